@@ -0,0 +1,20 @@
+#!/bin/sh
+# Regenerates docsrs_res.zip, the bindings and library used to build the documentation on
+# docs.rs, where the network is unavailable. Run it on x86_64 Linux after bumping the
+# TensorFlow TAG in build.rs or adding headers to the bindings.
+set -e
+root=$(cd "$(dirname "$0")/.." && pwd)
+cd "$root"
+cargo build --all-features
+out=$(ls -td target/debug/build/tflitec-*/out | while read -r dir; do
+    if [ -f "$dir/bindings.rs" ] && [ -f "$dir/libtensorflowlite_c.so" ]; then
+        echo "$dir"
+        break
+    fi
+done)
+if [ -z "$out" ]; then
+    echo "Cannot find the generated bindings and library" >&2
+    exit 1
+fi
+rm -f "$root/build-res/docsrs_res.zip"
+(cd "$out" && zip "$root/build-res/docsrs_res.zip" bindings.rs libtensorflowlite_c.so)
@@ -454,10 +454,13 @@ fn install_prebuilt(prebuilt_tflitec_path: &str, tf_src_path: &Path, lib_output_
     let mut headers = vec![
         "tensorflow/lite/c/c_api.h",
         "tensorflow/lite/c/c_api_types.h",
+        "tensorflow/lite/c/c_api_experimental.h",
+        "tensorflow/lite/c/c_api_opaque.h",
+        "tensorflow/lite/c/common.h",
+        "tensorflow/lite/builtin_ops.h",
     ];
     if cfg!(feature = "xnnpack") {
         headers.push("tensorflow/lite/delegates/xnnpack/xnnpack_delegate.h");
-        headers.push("tensorflow/lite/c/common.h");
     }
     copy_or_download_headers(tf_src_path, &headers);
 }
@@ -569,6 +572,9 @@ fn copy_or_overwrite<P: AsRef<Path> + Debug, Q: AsRef<Path> + Debug>(src: P, des
 // ------------------------------------------------------------------------
 
 /// If building on docs.rs (where the network is unavailable), extract pre-bundled resources (lib + bindings).
+///
+/// The bundle must be regenerated with `build-res/regenerate_docsrs_res.sh` whenever the bindings
+/// change, e.g. when headers are added to [`generate_bindings()`].
 fn prepare_for_docsrs() {
     let library_path = out_dir().join("libtensorflowlite_c.so");
     let bindings_path = out_dir().join("bindings.rs");
@@ -582,7 +588,9 @@ fn prepare_for_docsrs() {
     let success = unzip.status().map(|s| s.success()).unwrap_or(false);
 
     if !success || !library_path.exists() || !bindings_path.exists() {
-        panic!("Failed to extract docs.rs resources");
+        panic!(
+            "Failed to extract docs.rs resources, regenerate them with build-res/regenerate_docsrs_res.sh"
+        );
     }
 }
 
@@ -592,12 +600,25 @@ fn prepare_for_docsrs() {
 
 /// Generates bindings via bindgen (for TFLite C API, and xnnpack delegate if enabled).
 fn generate_bindings(tf_src_path: &Path) {
-    let mut builder = bindgen::Builder::default().header(
-        tf_src_path
-            .join("tensorflow/lite/c/c_api.h")
-            .to_string_lossy()
-            .to_string(),
-    );
+    let mut builder = bindgen::Builder::default()
+        .header(
+            tf_src_path
+                .join("tensorflow/lite/c/c_api.h")
+                .to_string_lossy()
+                .to_string(),
+        )
+        .header(
+            tf_src_path
+                .join("tensorflow/lite/c/c_api_experimental.h")
+                .to_string_lossy()
+                .to_string(),
+        )
+        .header(
+            tf_src_path
+                .join("tensorflow/lite/c/c_api_opaque.h")
+                .to_string_lossy()
+                .to_string(),
+        );
 
     if cfg!(feature = "xnnpack") {
         builder = builder.header(
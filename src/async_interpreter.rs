@@ -0,0 +1,415 @@
+//! An [`AsyncInterpreter`] running inference on dedicated threads for async services.
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::{mpsc, oneshot};
+
+use crate::interpreter::{CancellationToken, Interpreter, Options};
+use crate::model::ModelSource;
+use crate::tensor::Shape;
+use crate::worker::{self, InterpreterSetup, ModelWorker, WorkerConfig};
+use crate::{Error, ErrorKind, Result};
+
+/// The state of a request, shared by its future and the worker running it.
+enum JobState {
+    /// The request is queued, or its invocation is finished.
+    Queued,
+    /// The request is invoked by the [`Interpreter`] with the given token.
+    Running(Option<CancellationToken>),
+    /// The future of the request was dropped, so it is not invoked.
+    Dropped,
+}
+
+/// A request queued for the workers.
+struct Job {
+    inputs: Vec<Vec<u8>>,
+    state: Arc<Mutex<JobState>>,
+    reply: oneshot::Sender<Result<Vec<Vec<u8>>>>,
+}
+
+/// Skips or cancels the invocation of a request when its future is dropped.
+struct CancelOnDrop(Arc<Mutex<JobState>>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        let mut state = self.0.lock().unwrap();
+        if let JobState::Running(Some(token)) = &*state {
+            token.cancel();
+        }
+        *state = JobState::Dropped;
+    }
+}
+
+/// An inference worker owning its [`Model`][crate::model::Model] and [`Interpreter`] on dedicated
+/// threads, which can be used from async code without blocking the executor.
+///
+/// Requests are queued in a bounded queue, so [`AsyncInterpreter::run()`] waits for capacity
+/// when the workers fall behind, and [`AsyncInterpreter::try_run()`] fails instead.
+///
+/// Dropping the future of a request cancels it: a queued request is skipped, and an
+/// invocation in progress is cancelled if the [`Options`] enable cancellation, see
+/// [`OptionsBuilder::enable_cancellation()`][crate::interpreter::OptionsBuilder::enable_cancellation].
+///
+/// Inputs and outputs are the raw bytes of the input and output tensors, in their order.
+///
+/// # Examples
+///
+/// ```
+/// use tflitec::async_interpreter::AsyncInterpreter;
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let interpreter = AsyncInterpreter::builder().workers(2).build("tests/add.bin")?;
+/// let input = [1.0f32; 8 * 8 * 3]
+///     .iter()
+///     .flat_map(|x| x.to_ne_bytes())
+///     .collect::<Vec<u8>>();
+/// let outputs = interpreter.run(vec![input]).await?;
+/// assert_eq!(&outputs[0][..4], &3.0f32.to_ne_bytes());
+/// # Ok::<(), tflitec::Error>(())
+/// # }).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct AsyncInterpreter {
+    sender: mpsc::Sender<Job>,
+}
+
+impl AsyncInterpreter {
+    /// Creates a new [`AsyncInterpreterBuilder`].
+    pub fn builder() -> AsyncInterpreterBuilder {
+        AsyncInterpreterBuilder::default()
+    }
+
+    /// Runs inference with the given `inputs`, waiting for queue capacity if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the count of `inputs` does not match the inputs of the model, an input
+    /// does not match its tensor, the invocation fails or is cancelled, or the workers stopped.
+    pub async fn run(&self, inputs: Vec<Vec<u8>>) -> Result<Vec<Vec<u8>>> {
+        let (job, response) = Job::new(inputs);
+        let _cancel_on_drop = CancelOnDrop(job.state.clone());
+        self.sender
+            .send(job)
+            .await
+            .map_err(|_| Error::new(ErrorKind::InferenceWorkerStopped))?;
+        response
+            .await
+            .map_err(|_| Error::new(ErrorKind::InferenceWorkerStopped))?
+    }
+
+    /// Runs inference with the given `inputs` if the queue has capacity.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the queue is full, the count of `inputs` does not match the inputs of
+    /// the model, an input does not match its tensor, the invocation fails or is cancelled, or
+    /// the workers stopped.
+    pub async fn try_run(&self, inputs: Vec<Vec<u8>>) -> Result<Vec<Vec<u8>>> {
+        let (job, response) = Job::new(inputs);
+        let _cancel_on_drop = CancelOnDrop(job.state.clone());
+        self.sender.try_send(job).map_err(|error| match error {
+            mpsc::error::TrySendError::Full(_) => Error::new(ErrorKind::InferenceQueueFull),
+            mpsc::error::TrySendError::Closed(_) => Error::new(ErrorKind::InferenceWorkerStopped),
+        })?;
+        response
+            .await
+            .map_err(|_| Error::new(ErrorKind::InferenceWorkerStopped))?
+    }
+}
+
+impl Job {
+    fn new(inputs: Vec<Vec<u8>>) -> (Job, oneshot::Receiver<Result<Vec<Vec<u8>>>>) {
+        let (reply, response) = oneshot::channel();
+        let job = Job {
+            inputs,
+            state: Arc::new(Mutex::new(JobState::Queued)),
+            reply,
+        };
+        (job, response)
+    }
+}
+
+/// The builder of [`AsyncInterpreter`]s, see [`AsyncInterpreter::builder()`].
+#[derive(Debug)]
+pub struct AsyncInterpreterBuilder {
+    config: WorkerConfig,
+    workers: usize,
+}
+
+impl Default for AsyncInterpreterBuilder {
+    fn default() -> Self {
+        AsyncInterpreterBuilder {
+            config: WorkerConfig::new(32),
+            workers: 1,
+        }
+    }
+}
+
+impl AsyncInterpreterBuilder {
+    /// Sets the [`Options`] of the interpreters of all workers.
+    pub fn options(mut self, options: Options) -> Self {
+        self.config.set_options(options);
+        self
+    }
+
+    /// Sets the number of worker threads, each owning an interpreter.
+    ///
+    /// Defaults to 1.
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// Sets the number of requests which can be queued before
+    /// [`AsyncInterpreter::run()`] waits.
+    ///
+    /// Defaults to 32.
+    pub fn queue_capacity(mut self, queue_capacity: usize) -> Self {
+        self.config.set_queue_capacity(queue_capacity);
+        self
+    }
+
+    /// Resizes the input tensor at the given `index` of all interpreters to `shape` before
+    /// their tensors are allocated.
+    pub fn input_shape(mut self, index: usize, shape: Shape) -> Self {
+        self.config.add_input_shape(index, shape);
+        self
+    }
+
+    /// Returns the built [`AsyncInterpreter`] whose workers load the model at `filepath`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if a worker fails to load the model or to create its interpreter.
+    pub fn build(self, filepath: &str) -> Result<AsyncInterpreter> {
+        self.spawn(ModelSource::File(filepath.to_string()))
+    }
+
+    /// Returns the built [`AsyncInterpreter`] whose workers load the model from `bytes`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if a worker fails to load the model or to create its interpreter.
+    pub fn build_from_bytes(self, bytes: Vec<u8>) -> Result<AsyncInterpreter> {
+        self.spawn(ModelSource::Bytes(bytes))
+    }
+
+    fn spawn(self, source: ModelSource) -> Result<AsyncInterpreter> {
+        let (sender, receiver) = mpsc::channel(self.config.queue_capacity);
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..self.workers)
+            .map(|_| Worker {
+                setup: self.config.setup.clone(),
+                receiver: receiver.clone(),
+            })
+            .collect();
+        worker::spawn("tflitec-worker", source, workers)?;
+        Ok(AsyncInterpreter { sender })
+    }
+}
+
+/// A thread owning a [`Model`][crate::model::Model] and an [`Interpreter`], running queued requests.
+struct Worker {
+    setup: InterpreterSetup,
+    receiver: Arc<Mutex<mpsc::Receiver<Job>>>,
+}
+
+impl ModelWorker for Worker {
+    type Ready = ();
+
+    fn setup(&self) -> &InterpreterSetup {
+        &self.setup
+    }
+
+    fn ready(_interpreter: &Interpreter) -> Result<()> {
+        Ok(())
+    }
+
+    /// Runs requests until the [`AsyncInterpreter`] is dropped.
+    fn serve(self, interpreter: &Interpreter, _ready: ()) {
+        let token = interpreter.cancellation_token();
+        loop {
+            let job = match self.receiver.lock().unwrap().blocking_recv() {
+                Some(job) => job,
+                None => break,
+            };
+            {
+                // The state is checked and set under the same lock as in `CancelOnDrop`, so a
+                // future dropped at any time either skips the request or cancels its invocation.
+                let mut state = job.state.lock().unwrap();
+                if matches!(*state, JobState::Dropped) || job.reply.is_closed() {
+                    continue;
+                }
+                *state = JobState::Running(token.clone());
+            }
+            let result = Worker::invoke(interpreter, &job.inputs);
+            {
+                let mut state = job.state.lock().unwrap();
+                if let JobState::Running(_) = *state {
+                    *state = JobState::Queued;
+                }
+            }
+            let _ = job.reply.send(result);
+        }
+    }
+}
+
+impl Worker {
+    /// Runs `inputs` after checking there is one for each input tensor, so a partial request
+    /// is not invoked with the inputs of a previous one.
+    fn invoke(interpreter: &Interpreter, inputs: &[Vec<u8>]) -> Result<Vec<Vec<u8>>> {
+        let input_count = interpreter.input_tensor_count();
+        if inputs.len() != input_count {
+            return Err(Error::new(ErrorKind::InvalidInputCount(
+                inputs.len(),
+                input_count,
+            )));
+        }
+        for (index, input) in inputs.iter().enumerate() {
+            interpreter.copy(input, index)?;
+        }
+        interpreter.invoke()?;
+        (0..interpreter.output_tensor_count())
+            .map(|index| Ok(interpreter.output(index)?.data::<u8>().to_vec()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{mpsc, Arc, Mutex};
+
+    use crate::async_interpreter::AsyncInterpreter;
+    use crate::interpreter::Options;
+    use crate::profiler::TelemetryProfiler;
+    use crate::tensor::Shape;
+    use crate::worker::tests::to_bytes;
+    use crate::ErrorKind;
+
+    const MODEL_PATH: &str = "tests/add.bin";
+
+    #[test]
+    fn test_async_interpreter() {
+        let error = AsyncInterpreter::builder()
+            .build("tests/missing.bin")
+            .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::FailedToLoadModel);
+
+        let bytes = std::fs::read(MODEL_PATH).expect("Cannot read model data!");
+        let interpreter = AsyncInterpreter::builder()
+            .workers(2)
+            .queue_capacity(4)
+            .input_shape(0, Shape::new(vec![2, 8, 8, 3]))
+            .build_from_bytes(bytes)
+            .unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let requests = (0..8).map(|i| {
+                let data = (0..2 * 8 * 8 * 3)
+                    .map(|x| (x + i) as f32)
+                    .collect::<Vec<f32>>();
+                let interpreter = &interpreter;
+                async move {
+                    let outputs = interpreter.run(vec![to_bytes(&data)]).await.unwrap();
+                    let expected = data.iter().map(|x| x * 3.0).collect::<Vec<f32>>();
+                    assert_eq!(outputs, vec![to_bytes(&expected)]);
+                }
+            });
+            for request in requests.collect::<Vec<_>>() {
+                request.await;
+            }
+
+            let error = interpreter.try_run(vec![vec![0; 4]]).await.unwrap_err();
+            assert_eq!(
+                error.kind(),
+                ErrorKind::InvalidTensorDataCount(4, 2 * 8 * 8 * 3 * 4)
+            );
+            let error = interpreter.run(Vec::new()).await.unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidInputCount(0, 1));
+            let input = to_bytes(&[0.0; 2 * 8 * 8 * 3]);
+            let error = interpreter
+                .run(vec![input.clone(), input])
+                .await
+                .unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidInputCount(2, 1));
+        });
+    }
+
+    /// Records the invoked operators, and blocks the first operator of each invocation until it
+    /// is released.
+    struct BlockingProfiler {
+        op_indices: Mutex<Vec<i64>>,
+        entered: Mutex<mpsc::Sender<()>>,
+        release: Mutex<mpsc::Receiver<()>>,
+    }
+
+    impl TelemetryProfiler for BlockingProfiler {
+        fn begin_op_invoke(&self, _op_name: &str, op_index: i64, _subgraph_index: i64) -> u32 {
+            self.op_indices.lock().unwrap().push(op_index);
+            if op_index == 0 {
+                let _ = self.entered.lock().unwrap().send(());
+                let _ = self.release.lock().unwrap().recv();
+            }
+            0
+        }
+    }
+
+    #[test]
+    fn test_async_interpreter_dropped_requests() {
+        let (entered_sender, entered) = mpsc::channel();
+        let (release, release_receiver) = mpsc::channel();
+        let profiler = Arc::new(BlockingProfiler {
+            op_indices: Mutex::new(Vec::new()),
+            entered: Mutex::new(entered_sender),
+            release: Mutex::new(release_receiver),
+        });
+        let options = Options::builder()
+            .enable_cancellation(true)
+            .telemetry_profiler(profiler.clone())
+            .build()
+            .unwrap();
+        let interpreter = Arc::new(
+            AsyncInterpreter::builder()
+                .options(options)
+                .queue_capacity(1)
+                .build(MODEL_PATH)
+                .unwrap(),
+        );
+        let data = [1.0f32; 8 * 8 * 3];
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let spawn_run = || {
+                let interpreter = interpreter.clone();
+                tokio::spawn(async move { interpreter.run(vec![to_bytes(&data)]).await })
+            };
+            // The worker blocks in the first request, and the second one fills the queue.
+            let running = spawn_run();
+            entered.recv().unwrap();
+            let queued = spawn_run();
+            while interpreter.sender.capacity() > 0 {
+                tokio::task::yield_now().await;
+            }
+            let error = interpreter
+                .try_run(vec![to_bytes(&data)])
+                .await
+                .unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InferenceQueueFull);
+
+            // Dropping the futures skips the queued request and cancels the running one.
+            queued.abort();
+            assert!(queued.await.unwrap_err().is_cancelled());
+            running.abort();
+            assert!(running.await.unwrap_err().is_cancelled());
+            // Releases the cancelled request and the next one.
+            release.send(()).unwrap();
+            release.send(()).unwrap();
+            let outputs = interpreter.run(vec![to_bytes(&data)]).await.unwrap();
+            assert_eq!(outputs, vec![to_bytes(&[3.0; 8 * 8 * 3])]);
+        });
+        // The second operator of the cancelled request and the skipped request never ran.
+        assert_eq!(*profiler.op_indices.lock().unwrap(), vec![0, 0, 1]);
+    }
+}
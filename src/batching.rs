@@ -0,0 +1,589 @@
+//! Dynamic batching of single-sample requests, see [`Batcher`], and splitting of oversized
+//! batches, see [`run_in_batches()`].
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crate::interpreter::{Interpreter, Options};
+use crate::model::ModelSource;
+use crate::tensor::Shape;
+use crate::worker::{self, InterpreterSetup, ModelWorker, WorkerConfig};
+use crate::{Error, ErrorKind, Result};
+
+/// The byte layout of one sample of each input tensor of an [`Interpreter`].
+#[derive(Clone, Debug)]
+pub(crate) struct BatchLayout {
+    /// The dimensions of a sample of each input, i.e. without the batch dimension.
+    sample_dimensions: Vec<Vec<usize>>,
+
+    /// The byte count of a sample of each input.
+    sample_sizes: Vec<usize>,
+
+    /// The batch size the inputs are allocated with, or 0 after a failed resize so that the
+    /// inputs are resized again.
+    batch_size: usize,
+}
+
+impl BatchLayout {
+    /// Returns the layout of the allocated input tensors of `interpreter`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the first dimensions of the inputs and outputs, i.e. their batch sizes,
+    /// differ.
+    pub(crate) fn new(interpreter: &Interpreter) -> Result<BatchLayout> {
+        let mut sample_dimensions = Vec::new();
+        let mut sample_sizes = Vec::new();
+        let mut batch_size = None;
+        for index in 0..interpreter.input_tensor_count() {
+            let input = interpreter.input(index)?;
+            let dimensions = input.shape().dimensions();
+            let input_batch_size = dimensions.first().copied().unwrap_or(1).max(1);
+            let batch_size = *batch_size.get_or_insert(input_batch_size);
+            if input_batch_size != batch_size {
+                return Err(Error::new(ErrorKind::MismatchedBatchSize(
+                    index,
+                    input_batch_size,
+                    batch_size,
+                )));
+            }
+            sample_dimensions.push(dimensions.iter().skip(1).copied().collect());
+            sample_sizes.push(input.data::<u8>().len() / batch_size);
+        }
+        let batch_size = batch_size.unwrap_or(1);
+        BatchLayout::check_outputs(interpreter, batch_size)?;
+        Ok(BatchLayout {
+            sample_dimensions,
+            sample_sizes,
+            batch_size,
+        })
+    }
+
+    /// Checks that the outputs of `interpreter` have the batch size of the inputs, so that
+    /// they can be split into samples.
+    fn check_outputs(interpreter: &Interpreter, batch_size: usize) -> Result<()> {
+        for index in 0..interpreter.output_tensor_count() {
+            let output = interpreter.output(index)?;
+            let output_batch_size = output.shape().dimensions().first().copied().unwrap_or(1);
+            if output_batch_size != batch_size {
+                return Err(Error::new(ErrorKind::MismatchedOutputBatchSize(
+                    index,
+                    output_batch_size,
+                    batch_size,
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the batch size the inputs are allocated with.
+    pub(crate) fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Checks that `inputs` contain `sample_count` samples of each input.
+    pub(crate) fn validate(&self, inputs: &[&[u8]], sample_count: usize) -> Result<()> {
+        if inputs.len() != self.sample_sizes.len() {
+            return Err(Error::new(ErrorKind::InvalidInputCount(
+                inputs.len(),
+                self.sample_sizes.len(),
+            )));
+        }
+        for (input, sample_size) in inputs.iter().zip(self.sample_sizes.iter()) {
+            if input.len() != sample_size * sample_count {
+                return Err(Error::new(ErrorKind::InvalidTensorDataCount(
+                    input.len(),
+                    sample_size * sample_count,
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Resizes the inputs of `interpreter` to `batch_size` and allocates them, if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the inputs cannot be resized, or the outputs do not have the new batch
+    /// size.
+    pub(crate) fn resize(&mut self, interpreter: &Interpreter, batch_size: usize) -> Result<()> {
+        if batch_size == self.batch_size {
+            return Ok(());
+        }
+        let shapes = self
+            .sample_dimensions
+            .iter()
+            .map(|dimensions| {
+                let mut batch_dimensions = vec![batch_size];
+                batch_dimensions.extend_from_slice(dimensions);
+                Shape::new(batch_dimensions)
+            })
+            .collect::<Vec<_>>();
+        self.batch_size = 0;
+        worker::prepare(interpreter, shapes.iter().enumerate())?;
+        BatchLayout::check_outputs(interpreter, batch_size)?;
+        self.batch_size = batch_size;
+        Ok(())
+    }
+
+    /// Runs one invocation with the concatenation of `samples`, padded with zeros to the
+    /// allocated batch size, and returns the outputs of each sample.
+    ///
+    /// Each sample contains the bytes of one sample of each input, in input order.
+    pub(crate) fn invoke<S: AsRef<[u8]>>(
+        &self,
+        interpreter: &Interpreter,
+        samples: &[&[S]],
+    ) -> Result<Vec<Vec<Vec<u8>>>> {
+        for (index, sample_size) in self.sample_sizes.iter().enumerate() {
+            let mut data = Vec::with_capacity(sample_size * self.batch_size);
+            for sample in samples.iter() {
+                data.extend_from_slice(sample[index].as_ref());
+            }
+            data.resize(sample_size * self.batch_size, 0);
+            interpreter.copy(&data, index)?;
+        }
+        interpreter.invoke()?;
+        let mut outputs = vec![Vec::new(); samples.len()];
+        for index in 0..interpreter.output_tensor_count() {
+            let output = interpreter.output(index)?;
+            let data = output.data::<u8>();
+            let sample_size = data.len() / self.batch_size;
+            for (sample_index, sample_outputs) in outputs.iter_mut().enumerate() {
+                let start = sample_index * sample_size;
+                sample_outputs.push(data[start..start + sample_size].to_vec());
+            }
+        }
+        Ok(outputs)
+    }
+}
+
+/// Runs `inputs` with any number of samples in invocations of the batch size the inputs of
+/// `interpreter` are allocated with, and returns the outputs of all samples in order.
+///
+/// The samples are split into chunks of the batch size, and the last chunk is padded with
+/// zeros, so models with a fixed batch dimension can be used like dynamic batch ones.
+///
+/// Inputs and outputs are the raw bytes of the input and output tensors, in their order, and
+/// the samples of each are concatenated along the first dimension.
+///
+/// # Errors
+///
+/// Returns error if tensors are not allocated, the count of `inputs` does not match the inputs
+/// of the model, their byte counts are not the same multiple of a sample of each input, or an
+/// invocation fails.
+///
+/// # Examples
+///
+/// ```
+/// use tflitec::batching::run_in_batches;
+/// use tflitec::interpreter::Interpreter;
+/// use tflitec::model::Model;
+/// use tflitec::tensor::Shape;
+///
+/// let model = Model::new("tests/add.bin")?;
+/// let interpreter = Interpreter::new(&model, None)?;
+/// interpreter.resize_input(0, Shape::new(vec![4, 8, 8, 3]))?;
+/// interpreter.allocate_tensors()?;
+/// let input = [1.0f32; 10 * 8 * 8 * 3]
+///     .iter()
+///     .flat_map(|x| x.to_ne_bytes())
+///     .collect::<Vec<u8>>();
+/// let outputs = run_in_batches(&interpreter, &[&input])?;
+/// assert_eq!(outputs[0].len(), input.len());
+/// # Ok::<(), tflitec::Error>(())
+/// ```
+pub fn run_in_batches(interpreter: &Interpreter, inputs: &[&[u8]]) -> Result<Vec<Vec<u8>>> {
+    let layout = BatchLayout::new(interpreter)?;
+    let sample_count = match (inputs.first(), layout.sample_sizes.first()) {
+        (Some(input), Some(&sample_size)) if sample_size > 0 => input.len().div_ceil(sample_size),
+        _ => 0,
+    };
+    layout.validate(inputs, sample_count)?;
+    let mut outputs = vec![Vec::new(); interpreter.output_tensor_count()];
+    for start in (0..sample_count).step_by(layout.batch_size) {
+        let end = sample_count.min(start + layout.batch_size);
+        let samples = (start..end)
+            .map(|sample_index| {
+                inputs
+                    .iter()
+                    .zip(layout.sample_sizes.iter())
+                    .map(|(input, sample_size)| {
+                        &input[sample_index * sample_size..(sample_index + 1) * sample_size]
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let samples = samples.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        for sample_outputs in layout.invoke(interpreter, &samples)? {
+            for (output, data) in outputs.iter_mut().zip(sample_outputs) {
+                output.extend_from_slice(&data);
+            }
+        }
+    }
+    Ok(outputs)
+}
+
+/// A request of a single sample queued for the worker.
+struct Request {
+    inputs: Vec<Vec<u8>>,
+    reply: mpsc::Sender<Result<Vec<Vec<u8>>>>,
+}
+
+/// A batching layer which collects single-sample requests from many threads, and runs them
+/// in one [`Interpreter::invoke()`] call.
+///
+/// A batch is run when [`BatcherBuilder::max_batch_size()`] requests are collected, or
+/// [`BatcherBuilder::max_wait()`] passed since its first request. The samples of a batch are
+/// concatenated along the first dimension of each input, and the outputs are split back
+/// along the first dimension of each output.
+///
+/// Models with a dynamic batch dimension are resized to the size of each batch. Models with
+/// a fixed batch dimension are used with [`BatcherBuilder::fixed_batch()`], where smaller
+/// batches are padded with zeros.
+///
+/// Inputs and outputs are the raw bytes of one sample of the input and output tensors, in
+/// their order.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use tflitec::batching::Batcher;
+///
+/// let batcher = Batcher::builder()
+///     .max_batch_size(4)
+///     .max_wait(Duration::from_millis(5))
+///     .build("tests/add.bin")?;
+/// let input = [1.0f32; 8 * 8 * 3]
+///     .iter()
+///     .flat_map(|x| x.to_ne_bytes())
+///     .collect::<Vec<u8>>();
+/// let outputs = batcher.run(vec![input])?;
+/// assert_eq!(&outputs[0][..4], &3.0f32.to_ne_bytes());
+/// # Ok::<(), tflitec::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Batcher {
+    sender: mpsc::SyncSender<Request>,
+    layout: BatchLayout,
+}
+
+impl Batcher {
+    /// Creates a new [`BatcherBuilder`].
+    pub fn builder() -> BatcherBuilder {
+        BatcherBuilder::default()
+    }
+
+    /// Runs inference with the given `inputs` of a single sample as part of a batch, and
+    /// returns the outputs of the sample.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the count or byte count of `inputs` does not match the inputs of the
+    /// model, the invocation of the batch fails, or the worker stopped.
+    pub fn run(&self, inputs: Vec<Vec<u8>>) -> Result<Vec<Vec<u8>>> {
+        let samples = inputs.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        self.layout.validate(&samples, 1)?;
+        let (reply, response) = mpsc::channel();
+        self.sender
+            .send(Request { inputs, reply })
+            .map_err(|_| Error::new(ErrorKind::InferenceWorkerStopped))?;
+        response
+            .recv()
+            .map_err(|_| Error::new(ErrorKind::InferenceWorkerStopped))?
+    }
+}
+
+/// The builder of [`Batcher`]s, see [`Batcher::builder()`].
+#[derive(Debug)]
+pub struct BatcherBuilder {
+    config: WorkerConfig,
+    max_batch_size: usize,
+    max_wait: Duration,
+    fixed_batch: bool,
+}
+
+impl Default for BatcherBuilder {
+    fn default() -> Self {
+        BatcherBuilder {
+            config: WorkerConfig::new(64),
+            max_batch_size: 8,
+            max_wait: Duration::from_millis(5),
+            fixed_batch: false,
+        }
+    }
+}
+
+impl BatcherBuilder {
+    /// Sets the [`Options`] of the interpreter.
+    pub fn options(mut self, options: Options) -> Self {
+        self.config.set_options(options);
+        self
+    }
+
+    /// Sets the maximum number of requests of a batch.
+    ///
+    /// Defaults to 8. Batches of [fixed batch][BatcherBuilder::fixed_batch] models are also
+    /// limited to the batch size of the model.
+    pub fn max_batch_size(mut self, max_batch_size: usize) -> Self {
+        self.max_batch_size = max_batch_size.max(1);
+        self
+    }
+
+    /// Sets the maximum time to wait for more requests after the first request of a batch.
+    ///
+    /// Defaults to 5 milliseconds.
+    pub fn max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = max_wait;
+        self
+    }
+
+    /// Sets whether the batch dimension of the model is fixed, so batches are padded to it
+    /// instead of resizing the inputs.
+    ///
+    /// The batch size is the first dimension of the inputs after applying
+    /// [`BatcherBuilder::input_shape()`]s. Defaults to `false`.
+    pub fn fixed_batch(mut self, fixed_batch: bool) -> Self {
+        self.fixed_batch = fixed_batch;
+        self
+    }
+
+    /// Sets the number of requests which can be queued before [`Batcher::run()`] waits.
+    ///
+    /// Defaults to 64.
+    pub fn queue_capacity(mut self, queue_capacity: usize) -> Self {
+        self.config.set_queue_capacity(queue_capacity);
+        self
+    }
+
+    /// Resizes the input tensor at the given `index` to `shape` before its tensors are first
+    /// allocated.
+    pub fn input_shape(mut self, index: usize, shape: Shape) -> Self {
+        self.config.add_input_shape(index, shape);
+        self
+    }
+
+    /// Returns the built [`Batcher`] whose worker loads the model at `filepath`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the worker fails to load the model or to create its interpreter.
+    pub fn build(self, filepath: &str) -> Result<Batcher> {
+        self.spawn(ModelSource::File(filepath.to_string()))
+    }
+
+    /// Returns the built [`Batcher`] whose worker loads the model from `bytes`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the worker fails to load the model or to create its interpreter.
+    pub fn build_from_bytes(self, bytes: Vec<u8>) -> Result<Batcher> {
+        self.spawn(ModelSource::Bytes(bytes))
+    }
+
+    fn spawn(self, source: ModelSource) -> Result<Batcher> {
+        let (sender, receiver) = mpsc::sync_channel(self.config.queue_capacity);
+        let worker = BatchWorker {
+            setup: self.config.setup,
+            max_batch_size: self.max_batch_size,
+            max_wait: self.max_wait,
+            fixed_batch: self.fixed_batch,
+            receiver,
+        };
+        let layout = worker::spawn("tflitec-batcher", source, vec![worker])?.remove(0);
+        Ok(Batcher { sender, layout })
+    }
+}
+
+/// The thread of a [`Batcher`] owning its [`Model`][crate::model::Model] and [`Interpreter`].
+struct BatchWorker {
+    setup: InterpreterSetup,
+    max_batch_size: usize,
+    max_wait: Duration,
+    fixed_batch: bool,
+    receiver: mpsc::Receiver<Request>,
+}
+
+impl ModelWorker for BatchWorker {
+    type Ready = BatchLayout;
+
+    fn setup(&self) -> &InterpreterSetup {
+        &self.setup
+    }
+
+    fn ready(interpreter: &Interpreter) -> Result<BatchLayout> {
+        BatchLayout::new(interpreter)
+    }
+
+    /// Runs batches of requests until the [`Batcher`] is dropped.
+    fn serve(self, interpreter: &Interpreter, mut layout: BatchLayout) {
+        let max_batch_size = if self.fixed_batch {
+            self.max_batch_size.min(layout.batch_size())
+        } else {
+            self.max_batch_size
+        };
+        while let Ok(first) = self.receiver.recv() {
+            let mut batch = vec![first];
+            let deadline = Instant::now() + self.max_wait;
+            while batch.len() < max_batch_size {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                match self.receiver.recv_timeout(timeout) {
+                    Ok(request) => batch.push(request),
+                    Err(_) => break,
+                }
+            }
+            let result = if self.fixed_batch {
+                Ok(())
+            } else {
+                layout.resize(interpreter, batch.len())
+            }
+            .and_then(|_| {
+                let samples = batch
+                    .iter()
+                    .map(|request| request.inputs.as_slice())
+                    .collect::<Vec<_>>();
+                layout.invoke(interpreter, &samples)
+            });
+            match result {
+                Ok(outputs) => {
+                    for (request, outputs) in batch.into_iter().zip(outputs) {
+                        let _ = request.reply.send(Ok(outputs));
+                    }
+                }
+                Err(error) => {
+                    for request in batch {
+                        let _ = request.reply.send(Err(error.clone()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::batching::{run_in_batches, BatchLayout, Batcher};
+    use crate::interpreter::Interpreter;
+    use crate::model::Model;
+    use crate::tensor::Shape;
+    use crate::worker::{self, tests::to_bytes};
+    use crate::ErrorKind;
+
+    const MODEL_PATH: &str = "tests/add.bin";
+    const CONSTANT_BATCH_MODEL_PATH: &str = "tests/constant_batch.bin";
+    const SAMPLE_SIZE: usize = 8 * 8 * 3;
+
+    fn run_concurrently(batcher: &Batcher) {
+        std::thread::scope(|scope| {
+            for i in 0..6 {
+                scope.spawn(move || {
+                    for j in 0..4 {
+                        let data = (0..SAMPLE_SIZE)
+                            .map(|x| (x + i * 10 + j) as f32)
+                            .collect::<Vec<f32>>();
+                        let outputs = batcher.run(vec![to_bytes(&data)]).unwrap();
+                        let expected = data.iter().map(|x| x * 3.0).collect::<Vec<f32>>();
+                        assert_eq!(outputs, vec![to_bytes(&expected)]);
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_batcher() {
+        let batcher = Batcher::builder()
+            .max_batch_size(4)
+            .max_wait(Duration::from_millis(10))
+            .build(MODEL_PATH)
+            .unwrap();
+        run_concurrently(&batcher);
+
+        let error = batcher.run(vec![vec![0; 4]]).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            ErrorKind::InvalidTensorDataCount(4, SAMPLE_SIZE * 4)
+        );
+        let error = batcher.run(Vec::new()).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInputCount(0, 1));
+
+        let error = Batcher::builder().build("tests/broadcast.bin").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::MismatchedBatchSize(1, 1, 2));
+    }
+
+    #[test]
+    fn test_batcher_fixed_batch() {
+        let batcher = Batcher::builder()
+            .fixed_batch(true)
+            .input_shape(0, Shape::new(vec![3, 8, 8, 3]))
+            .max_wait(Duration::from_millis(10))
+            .build(MODEL_PATH)
+            .unwrap();
+        run_concurrently(&batcher);
+    }
+
+    #[test]
+    fn test_run_in_batches() {
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let interpreter = Interpreter::new(&model, None).unwrap();
+        interpreter
+            .resize_input(0, Shape::new(vec![4, 8, 8, 3]))
+            .unwrap();
+        interpreter.allocate_tensors().unwrap();
+
+        let data = (0..10 * SAMPLE_SIZE)
+            .map(|x| x as f32)
+            .collect::<Vec<f32>>();
+        let outputs = run_in_batches(&interpreter, &[&to_bytes(&data)]).unwrap();
+        let expected = data.iter().map(|x| x * 3.0).collect::<Vec<f32>>();
+        assert_eq!(outputs, vec![to_bytes(&expected)]);
+
+        let outputs = run_in_batches(&interpreter, &[&[]]).unwrap();
+        assert_eq!(outputs, vec![Vec::<u8>::new()]);
+
+        let error = run_in_batches(&interpreter, &[&[0; 4]]).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            ErrorKind::InvalidTensorDataCount(4, SAMPLE_SIZE * 4)
+        );
+    }
+
+    #[test]
+    fn test_batch_layout_outputs() {
+        let model = Model::new(CONSTANT_BATCH_MODEL_PATH).expect("Cannot load model from file!");
+        let interpreter = Interpreter::new(&model, None).unwrap();
+        interpreter.allocate_tensors().unwrap();
+        let error = BatchLayout::new(&interpreter).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::MismatchedOutputBatchSize(0, 2, 1));
+
+        worker::prepare(&interpreter, vec![(0, &Shape::new(vec![2, 4]))]).unwrap();
+        let mut layout = BatchLayout::new(&interpreter).unwrap();
+        assert_eq!(layout.batch_size(), 2);
+        let error = layout.resize(&interpreter, 1).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::MismatchedOutputBatchSize(0, 2, 1));
+        assert_eq!(layout.batch_size(), 0);
+        // The input cannot be broadcast to the constant, and the next resize prepares again.
+        assert!(layout.resize(&interpreter, 3).is_err());
+        assert_eq!(layout.batch_size(), 0);
+        layout.resize(&interpreter, 2).unwrap();
+        assert_eq!(
+            interpreter.input(0).unwrap().shape().dimensions(),
+            &vec![2, 4]
+        );
+
+        let first = to_bytes(&[1.0, 1.0, 1.0, 1.0]);
+        let second = to_bytes(&[2.0, 2.0, 2.0, 2.0]);
+        let outputs = layout
+            .invoke(&interpreter, &[&[&first], &[&second]])
+            .unwrap();
+        assert_eq!(
+            outputs,
+            vec![
+                vec![to_bytes(&[1.0, 2.0, 3.0, 4.0])],
+                vec![to_bytes(&[6.0, 7.0, 8.0, 9.0])],
+            ]
+        );
+    }
+}
@@ -0,0 +1,248 @@
+//! An [`InterpreterCache`] keeping allocated interpreters of recurring input shapes.
+use std::fmt::{Debug, Formatter};
+
+use crate::interpreter::{Interpreter, Options};
+use crate::model::Model;
+use crate::tensor::Shape;
+use crate::worker;
+use crate::{Error, ErrorKind, Result};
+
+/// A cache of [`Interpreter`]s of the same [`Model`], keeping one allocated interpreter per
+/// distinct tuple of input shapes.
+///
+/// Resizing inputs and allocating tensors is relatively expensive, so workloads with a few
+/// recurring input shapes route each call to the interpreter allocated for its shapes with
+/// [`InterpreterCache::get()`]. When the cache is full, the least recently used interpreter
+/// is resized and reallocated for the new shapes.
+///
+/// # Examples
+///
+/// ```
+/// use tflitec::cache::InterpreterCache;
+/// use tflitec::model::Model;
+/// use tflitec::tensor::Shape;
+///
+/// let model = Model::new("tests/add.bin")?;
+/// let mut cache = InterpreterCache::new(&model, None, 2);
+/// for batch_size in [1, 2, 1, 2] {
+///     let shape = Shape::new(vec![batch_size, 8, 8, 3]);
+///     let interpreter = cache.get(&[shape])?;
+///     interpreter.copy(&vec![1.0f32; batch_size * 8 * 8 * 3], 0)?;
+///     interpreter.invoke()?;
+/// }
+/// assert_eq!(cache.len(), 2);
+/// # Ok::<(), tflitec::Error>(())
+/// ```
+pub struct InterpreterCache<'a> {
+    model: &'a Model<'a>,
+    options: Option<Options>,
+    capacity: usize,
+
+    /// The interpreters keyed by the dimensions of their inputs, least recently used first.
+    entries: Vec<(Vec<Vec<usize>>, Interpreter<'a>)>,
+}
+
+impl Debug for InterpreterCache<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InterpreterCache")
+            .field("options", &self.options)
+            .field("capacity", &self.capacity)
+            .field(
+                "shapes",
+                &self.entries.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl<'a> InterpreterCache<'a> {
+    /// Creates a new cache of interpreters of `model` with `options`, keeping at most
+    /// `capacity` interpreters.
+    pub fn new(model: &'a Model<'a>, options: Option<Options>, capacity: usize) -> Self {
+        InterpreterCache {
+            model,
+            options,
+            capacity: capacity.max(1),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns the maximum number of interpreters of the cache.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of interpreters of the cache.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the cache has no interpreters.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns whether the cache has an interpreter allocated for the given input `shapes`.
+    pub fn contains(&self, shapes: &[Shape]) -> bool {
+        let key = InterpreterCache::key(shapes);
+        self.entries.iter().any(|(entry_key, _)| *entry_key == key)
+    }
+
+    /// Removes all interpreters of the cache.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the interpreter allocated for the given `shapes` of all inputs, in order.
+    ///
+    /// The interpreter is created, or the least recently used one is reallocated, if no
+    /// interpreter of the cache is allocated for `shapes`. If the least recently used one
+    /// cannot be reallocated for `shapes`, it is kept for its previous shapes. If the inputs of
+    /// the interpreter allocated for `shapes` were resized since it was returned, they are
+    /// resized back, and it is removed from the cache if that fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the count of `shapes` does not match the inputs of the model, or an
+    /// interpreter cannot be created, resized or allocated.
+    pub fn get(&mut self, shapes: &[Shape]) -> Result<&Interpreter<'a>> {
+        let key = InterpreterCache::key(shapes);
+        if let Some(position) = self
+            .entries
+            .iter()
+            .position(|(entry_key, _)| *entry_key == key)
+        {
+            let (entry_key, interpreter) = self.entries.remove(position);
+            // The inputs may have been resized through a reference returned earlier.
+            let input_shapes = worker::input_shapes(&interpreter);
+            if !input_shapes.is_ok_and(|input_shapes| input_shapes == shapes) {
+                worker::prepare(&interpreter, shapes.iter().enumerate())?;
+            }
+            self.entries.push((entry_key, interpreter));
+        } else {
+            let (evicted_key, interpreter) = if self.entries.len() >= self.capacity {
+                InterpreterCache::validate(&self.entries[0].1, shapes)?;
+                let (evicted_key, interpreter) = self.entries.remove(0);
+                (Some(evicted_key), interpreter)
+            } else {
+                let interpreter = Interpreter::new(self.model, self.options.clone())?;
+                InterpreterCache::validate(&interpreter, shapes)?;
+                (None, interpreter)
+            };
+            if let Err(error) = worker::prepare(&interpreter, shapes.iter().enumerate()) {
+                if let Some(evicted_key) = evicted_key {
+                    self.restore(evicted_key, interpreter);
+                }
+                return Err(error);
+            }
+            self.entries.push((key, interpreter));
+        }
+        Ok(&self.entries[self.entries.len() - 1].1)
+    }
+
+    /// Reallocates an evicted interpreter for the shapes of its `key` and puts it back as the
+    /// least recently used one, or drops it if it cannot be reallocated.
+    fn restore(&mut self, key: Vec<Vec<usize>>, interpreter: Interpreter<'a>) {
+        let shapes = key.iter().cloned().map(Shape::new).collect::<Vec<_>>();
+        if worker::prepare(&interpreter, shapes.iter().enumerate()).is_ok() {
+            self.entries.insert(0, (key, interpreter));
+        }
+    }
+
+    fn key(shapes: &[Shape]) -> Vec<Vec<usize>> {
+        shapes
+            .iter()
+            .map(|shape| shape.dimensions().clone())
+            .collect()
+    }
+
+    /// Checks that there is one shape for each input of `interpreter`.
+    fn validate(interpreter: &Interpreter, shapes: &[Shape]) -> Result<()> {
+        let input_count = interpreter.input_tensor_count();
+        if shapes.len() != input_count {
+            return Err(Error::new(ErrorKind::InvalidInputCount(
+                shapes.len(),
+                input_count,
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::InterpreterCache;
+    use crate::model::Model;
+    use crate::tensor::Shape;
+    use crate::ErrorKind;
+
+    const MODEL_PATH: &str = "tests/add.bin";
+
+    #[test]
+    fn test_interpreter_cache() {
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let mut cache = InterpreterCache::new(&model, None, 2);
+        let shape = |batch_size| vec![Shape::new(vec![batch_size, 8, 8, 3])];
+
+        for &batch_size in [1, 2, 1, 3].iter() {
+            let interpreter = cache.get(&shape(batch_size)).unwrap();
+            let data = (0..batch_size * 8 * 8 * 3)
+                .map(|x| x as f32)
+                .collect::<Vec<f32>>();
+            interpreter.copy(&data, 0).unwrap();
+            interpreter.invoke().unwrap();
+            let expected = data.iter().map(|x| x * 3.0).collect::<Vec<f32>>();
+            assert_eq!(interpreter.output(0).unwrap().data::<f32>(), expected);
+        }
+        assert_eq!(cache.len(), 2);
+        assert!(cache.contains(&shape(1)));
+        assert!(!cache.contains(&shape(2)));
+        assert!(cache.contains(&shape(3)));
+
+        let error = cache.get(&[]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInputCount(0, 1));
+        assert_eq!(cache.len(), 2);
+
+        // Inputs resized through a returned interpreter are resized back.
+        let interpreter = cache.get(&shape(1)).unwrap();
+        interpreter
+            .resize_input(0, Shape::new(vec![2, 8, 8, 3]))
+            .unwrap();
+        interpreter.allocate_tensors().unwrap();
+        let interpreter = cache.get(&shape(1)).unwrap();
+        assert_eq!(
+            interpreter.input(0).unwrap().shape().dimensions(),
+            &vec![1, 8, 8, 3]
+        );
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_interpreter_cache_failed_reallocation() {
+        let model = Model::new("tests/broadcast.bin").expect("Cannot load model from file!");
+        let mut cache = InterpreterCache::new(&model, None, 1);
+        let shapes = |batch_size, row_count| {
+            vec![
+                Shape::new(vec![batch_size, 4]),
+                Shape::new(vec![row_count, 4]),
+            ]
+        };
+        cache.get(&shapes(2, 1)).unwrap();
+
+        let error = cache.get(&shapes(2, 3)).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::FailedToAllocateTensors);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.contains(&shapes(2, 1)));
+
+        let interpreter = cache.get(&shapes(2, 1)).unwrap();
+        interpreter.copy(&[1.0f32; 8], 0).unwrap();
+        interpreter.copy(&[1.0f32, 2.0, 3.0, 4.0], 1).unwrap();
+        interpreter.invoke().unwrap();
+        assert_eq!(
+            interpreter.output(0).unwrap().data::<f32>(),
+            &[2.0, 3.0, 4.0, 5.0, 2.0, 3.0, 4.0, 5.0]
+        );
+    }
+}
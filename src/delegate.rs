@@ -0,0 +1,1506 @@
+//! TensorFlow Lite delegates that can be attached to the [`Interpreter`][crate::interpreter::Interpreter].
+//!
+//! Delegates are attached through [`Options`][crate::interpreter::Options] as
+//! [`DelegateFactory`] instances, so the same options can be used to create several
+//! interpreters. Each [`Interpreter`][crate::interpreter::Interpreter] creates its own
+//! delegate instances and deletes them when it is dropped.
+//!
+//! Delegates can be loaded from shared libraries with `ExternalDelegate` (requires the
+//! `external_delegate` feature), or implemented in Rust with the [`Delegate`] trait, see
+//! [`OpaqueDelegateFactory`] and the reference [`ArithmeticDelegate`].
+#[cfg(feature = "external_delegate")]
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{c_void, CStr, CString};
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::os::raw::{c_char, c_int};
+#[cfg(feature = "external_delegate")]
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+#[cfg(feature = "external_delegate")]
+use libloading::Library;
+
+use crate::bindings::*;
+#[cfg(feature = "external_delegate")]
+use crate::interpreter::ErrorReporter;
+use crate::tensor::{DataType, Shape, TensorElement};
+use crate::{Error, ErrorKind, Result};
+
+/// A factory of delegates attached to the [`Interpreter`][crate::interpreter::Interpreter].
+pub trait DelegateFactory: Send + Sync {
+    /// Returns the name of delegates created by this factory.
+    fn name(&self) -> &str;
+
+    /// Creates a new delegate instance for a single
+    /// [`Interpreter`][crate::interpreter::Interpreter].
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the delegate cannot be created.
+    fn create_delegate(&self) -> Result<RawDelegate>;
+}
+
+/// An owned, type-erased TensorFlow Lite delegate instance.
+///
+/// The delegate is deleted with the deleter given to [`RawDelegate::from_raw()`]
+/// when this instance is dropped.
+pub struct RawDelegate {
+    /// The underlying `TfLiteDelegate` (or `TfLiteOpaqueDelegate`) C pointer.
+    delegate_ptr: *mut c_void,
+
+    /// The function deleting `delegate_ptr`.
+    deleter: Option<Box<dyn FnOnce(*mut c_void) + Send>>,
+}
+
+impl Debug for RawDelegate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RawDelegate")
+            .field("delegate_ptr", &self.delegate_ptr)
+            .finish()
+    }
+}
+
+unsafe impl Send for RawDelegate {}
+
+impl RawDelegate {
+    /// Creates a new instance from the given C pointer and its `deleter`.
+    ///
+    /// # Arguments
+    ///
+    /// * `delegate_ptr`: A pointer to a `TfLiteDelegate` or `TfLiteOpaqueDelegate`.
+    /// * `deleter`: A function deleting the delegate, called once when this instance is dropped.
+    ///
+    /// # Safety
+    ///
+    /// `delegate_ptr` must point to a valid delegate which stays valid until `deleter` is called.
+    pub unsafe fn from_raw<F>(delegate_ptr: *mut c_void, deleter: F) -> RawDelegate
+    where
+        F: FnOnce(*mut c_void) + Send + 'static,
+    {
+        RawDelegate {
+            delegate_ptr,
+            deleter: Some(Box::new(deleter)),
+        }
+    }
+
+    /// Returns the underlying C pointer.
+    pub fn as_ptr(&self) -> *mut c_void {
+        self.delegate_ptr
+    }
+}
+
+impl Drop for RawDelegate {
+    fn drop(&mut self) {
+        if let Some(deleter) = self.deleter.take() {
+            deleter(self.delegate_ptr)
+        }
+    }
+}
+
+/// The `tflite_plugin_create_delegate` function of external delegate libraries.
+#[cfg(feature = "external_delegate")]
+type CreatePluginDelegate = unsafe extern "C" fn(
+    options_keys: *mut *mut c_char,
+    options_values: *mut *mut c_char,
+    num_options: usize,
+    report_error: Option<unsafe extern "C" fn(*const c_char)>,
+) -> *mut c_void;
+
+/// The `tflite_plugin_destroy_delegate` function of external delegate libraries.
+#[cfg(feature = "external_delegate")]
+type DestroyPluginDelegate = unsafe extern "C" fn(delegate: *mut c_void);
+
+/// A [`DelegateFactory`] of delegates loaded from a shared library at runtime.
+///
+/// The library must export the functions of TensorFlow Lite external delegate plugins:
+///
+/// ```c
+/// TfLiteDelegate* tflite_plugin_create_delegate(
+///     char** options_keys, char** options_values, size_t num_options,
+///     void (*report_error)(const char*));
+/// void tflite_plugin_destroy_delegate(TfLiteDelegate* delegate);
+/// ```
+///
+/// The library stays loaded until this instance and all delegates it created are dropped.
+///
+/// Errors reported by the library while creating a delegate are forwarded to the error
+/// reporter of the [`Interpreter`][crate::interpreter::Interpreter] being created, see
+/// [`OptionsBuilder::error_reporter()`][crate::interpreter::OptionsBuilder::error_reporter].
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use tflitec::delegate::ExternalDelegate;
+/// use tflitec::interpreter::{Interpreter, Options};
+/// use tflitec::model::Model;
+/// let delegate = unsafe { ExternalDelegate::new("libmy_delegate.so")? }
+///     .option("num_threads", "2");
+/// let options = Options::builder().add_delegate(Arc::new(delegate)).build()?;
+/// let model = Model::new("tests/add.bin")?;
+/// let interpreter = Interpreter::new(&model, Some(options))?;
+/// # Ok::<(), tflitec::Error>(())
+/// ```
+#[cfg(feature = "external_delegate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "external_delegate")))]
+#[derive(Clone, Debug)]
+pub struct ExternalDelegate {
+    /// The name of the delegate, i.e., the file name of the library.
+    name: String,
+
+    /// The loaded library.
+    library: Arc<Library>,
+
+    /// The options passed to `tflite_plugin_create_delegate`.
+    options: Vec<(String, String)>,
+}
+
+#[cfg(feature = "external_delegate")]
+impl ExternalDelegate {
+    /// Loads the external delegate library at the given path.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the library cannot be loaded or does not export the delegate
+    /// plugin functions.
+    ///
+    /// # Safety
+    ///
+    /// Loading a library runs its initialization routines, and its plugin functions must
+    /// match the signatures above. See [`libloading::Library::new()`].
+    pub unsafe fn new<P: AsRef<Path>>(library_path: P) -> Result<ExternalDelegate> {
+        let library_path = library_path.as_ref();
+        let library = Library::new(library_path)
+            .map_err(|_| Error::new(ErrorKind::FailedToLoadDelegateLibrary))?;
+        library
+            .get::<CreatePluginDelegate>(b"tflite_plugin_create_delegate\0")
+            .and(library.get::<DestroyPluginDelegate>(b"tflite_plugin_destroy_delegate\0"))
+            .map_err(|_| Error::new(ErrorKind::FailedToLoadDelegateLibrary))?;
+        let name = library_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Ok(ExternalDelegate {
+            name,
+            library: Arc::new(library),
+            options: Vec::new(),
+        })
+    }
+
+    /// Adds an option passed to the delegate when it is created.
+    pub fn option<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.options.push((key.into(), value.into()));
+        self
+    }
+
+    /// Returns the options passed to the delegate when it is created.
+    pub fn options(&self) -> &[(String, String)] {
+        &self.options
+    }
+}
+
+#[cfg(feature = "external_delegate")]
+impl DelegateFactory for ExternalDelegate {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn create_delegate(&self) -> Result<RawDelegate> {
+        let to_cstring = |value: &String| {
+            CString::new(value.as_str()).map_err(|_| Error::new(ErrorKind::FailedToCreateDelegate))
+        };
+        let keys = self
+            .options
+            .iter()
+            .map(|(key, _)| to_cstring(key))
+            .collect::<Result<Vec<_>>>()?;
+        let values = self
+            .options
+            .iter()
+            .map(|(_, value)| to_cstring(value))
+            .collect::<Result<Vec<_>>>()?;
+        let mut key_ptrs = keys
+            .iter()
+            .map(|key| key.as_ptr() as *mut c_char)
+            .collect::<Vec<_>>();
+        let mut value_ptrs = values
+            .iter()
+            .map(|value| value.as_ptr() as *mut c_char)
+            .collect::<Vec<_>>();
+        unsafe {
+            let create = self
+                .library
+                .get::<CreatePluginDelegate>(b"tflite_plugin_create_delegate\0")
+                .map_err(|_| Error::new(ErrorKind::FailedToLoadDelegateLibrary))?;
+            let delegate_ptr = create(
+                key_ptrs.as_mut_ptr(),
+                value_ptrs.as_mut_ptr(),
+                self.options.len(),
+                Some(report_plugin_error),
+            );
+            if delegate_ptr.is_null() {
+                return Err(Error::new(ErrorKind::FailedToCreateDelegate));
+            }
+            let library = self.library.clone();
+            Ok(RawDelegate::from_raw(delegate_ptr, move |delegate_ptr| {
+                if let Ok(destroy) =
+                    library.get::<DestroyPluginDelegate>(b"tflite_plugin_destroy_delegate\0")
+                {
+                    destroy(delegate_ptr)
+                }
+            }))
+        }
+    }
+}
+
+#[cfg(feature = "external_delegate")]
+thread_local! {
+    /// The error reporter of the interpreter whose delegates are created on this thread.
+    static PLUGIN_ERROR_REPORTER: RefCell<Option<ErrorReporter>> = RefCell::new(None);
+}
+
+/// Calls `f`, which creates the delegates of an interpreter, forwarding the errors reported by
+/// external delegates to `error_reporter`.
+#[cfg(feature = "external_delegate")]
+pub(crate) fn with_plugin_error_reporter<R>(
+    error_reporter: Option<&ErrorReporter>,
+    f: impl FnOnce() -> R,
+) -> R {
+    let previous = PLUGIN_ERROR_REPORTER.with(|reporter| reporter.replace(error_reporter.cloned()));
+    let result = f();
+    PLUGIN_ERROR_REPORTER.with(|reporter| reporter.replace(previous));
+    result
+}
+
+/// Forwards the errors reported by external delegates to the error reporter of the interpreter
+/// being created, or prints them as TensorFlow Lite does by default.
+#[cfg(feature = "external_delegate")]
+unsafe extern "C" fn report_plugin_error(message: *const c_char) {
+    if message.is_null() {
+        return;
+    }
+    let message = CStr::from_ptr(message).to_string_lossy();
+    PLUGIN_ERROR_REPORTER.with(|reporter| match reporter.borrow().as_ref() {
+        Some(reporter) => crate::catch_unwind((), || reporter(&message)),
+        None => eprintln!("{}", message),
+    })
+}
+
+/// A delegate implemented in Rust.
+///
+/// When the delegate is applied to an [`Interpreter`][crate::interpreter::Interpreter],
+/// [`Delegate::is_node_supported()`] is called for each node of the execution plan.
+/// TensorFlow Lite groups the supported nodes into partitions and replaces each partition with
+/// a single kernel created with [`Delegate::create_kernel()`].
+///
+/// Attach it to [`Options`][crate::interpreter::Options] with an [`OpaqueDelegateFactory`].
+///
+/// - Note: Panics of the delegate and its kernels are caught and reported to TensorFlow Lite
+/// as errors, since they cannot unwind through TensorFlow Lite C.
+pub trait Delegate: Send + Sync {
+    /// Returns the name of the delegate.
+    fn name(&self) -> &str;
+
+    /// Returns whether the delegate can run the given `node`.
+    fn is_node_supported(&self, context: &DelegateContext, node: &Node) -> bool;
+
+    /// Creates the kernel running the nodes of the given `partition`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the kernel cannot be created, which fails applying the delegate.
+    fn create_kernel(
+        &self,
+        context: &DelegateContext,
+        partition: &Partition,
+    ) -> Result<Box<dyn DelegateKernel>>;
+}
+
+/// A kernel running a [`Partition`] of nodes claimed by a [`Delegate`].
+pub trait DelegateKernel: Send {
+    /// Prepares the kernel, called when tensors are allocated.
+    ///
+    /// Kernels should validate the shapes of their tensors and resize their outputs here.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the kernel cannot run with the current tensors.
+    fn prepare(&mut self, _context: &mut DelegateContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// Evaluates the partition, called on each [`invoke()`][crate::interpreter::Interpreter::invoke].
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the evaluation fails, which fails the invocation.
+    fn eval(&mut self, context: &mut DelegateContext) -> Result<()>;
+}
+
+/// Access to the tensors of the graph a [`Delegate`] is applied to.
+pub struct DelegateContext<'a> {
+    /// The underlying `TfLiteOpaqueContext` C pointer.
+    context_ptr: *mut TfLiteOpaqueContext,
+    phantom: PhantomData<&'a ()>,
+}
+
+impl Debug for DelegateContext<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DelegateContext")
+            .field("context_ptr", &self.context_ptr)
+            .finish()
+    }
+}
+
+impl DelegateContext<'_> {
+    fn from_raw(context_ptr: *mut TfLiteOpaqueContext) -> Self {
+        DelegateContext {
+            context_ptr,
+            phantom: PhantomData,
+        }
+    }
+
+    fn tensor_ptr(&self, index: usize) -> Result<*mut TfLiteOpaqueTensor> {
+        let tensor_ptr =
+            unsafe { TfLiteOpaqueContextGetOpaqueTensor(self.context_ptr, index as c_int) };
+        if tensor_ptr.is_null() {
+            Err(Error::new(ErrorKind::ReadTensorError))
+        } else {
+            Ok(tensor_ptr)
+        }
+    }
+
+    /// Returns the [data type][`DataType`] of the tensor at the given index.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if there is no tensor at `index` or its data type is unsupported.
+    pub fn tensor_data_type(&self, index: usize) -> Result<DataType> {
+        let tensor_ptr = self.tensor_ptr(index)?;
+        DataType::new(unsafe { TfLiteOpaqueTensorType(tensor_ptr) })
+            .ok_or_else(|| Error::new(ErrorKind::InvalidTensorDataType))
+    }
+
+    /// Returns the [`Shape`] of the tensor at the given index.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if there is no tensor at `index`.
+    pub fn tensor_shape(&self, index: usize) -> Result<Shape> {
+        let tensor_ptr = self.tensor_ptr(index)?;
+        let dimensions = unsafe {
+            let rank = TfLiteOpaqueTensorNumDims(tensor_ptr);
+            (0..rank)
+                .map(|i| TfLiteOpaqueTensorDim(tensor_ptr, i) as usize)
+                .collect()
+        };
+        Ok(Shape::new(dimensions))
+    }
+
+    /// Returns data of the tensor at the given index as a slice of given type `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if there is no tensor at `index`, its buffer is not allocated or
+    /// its number of bytes is not integer multiple of byte count of a single `T`.
+    pub fn tensor_data<T: TensorElement>(&self, index: usize) -> Result<&[T]> {
+        let tensor_ptr = self.tensor_ptr(index)?;
+        let element_size = std::mem::size_of::<T>();
+        unsafe {
+            let data_ptr = TfLiteOpaqueTensorData(tensor_ptr);
+            let data_length = TfLiteOpaqueTensorByteSize(tensor_ptr);
+            if data_ptr.is_null() || data_length % element_size != 0 {
+                return Err(Error::new(ErrorKind::ReadTensorError));
+            }
+            Ok(std::slice::from_raw_parts(
+                data_ptr as *const T,
+                data_length / element_size,
+            ))
+        }
+    }
+
+    /// Sets data of the tensor at the given index by copying given data slice.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if there is no tensor at `index`, its buffer is not allocated or
+    /// byte count of the data does not match its buffer size.
+    pub fn set_tensor_data<T: TensorElement>(&mut self, index: usize, data: &[T]) -> Result<()> {
+        let tensor_ptr = self.tensor_ptr(index)?;
+        let element_size = std::mem::size_of::<T>();
+        unsafe {
+            let data_ptr = TfLiteOpaqueTensorData(tensor_ptr);
+            let data_length = TfLiteOpaqueTensorByteSize(tensor_ptr);
+            if data_ptr.is_null() {
+                return Err(Error::new(ErrorKind::ReadTensorError));
+            }
+            if data_length != element_size * data.len() {
+                return Err(Error::new(ErrorKind::InvalidTensorDataCount(
+                    data.len(),
+                    data_length / element_size,
+                )));
+            }
+            std::ptr::copy_nonoverlapping(
+                data.as_ptr() as *const u8,
+                data_ptr as *mut u8,
+                data_length,
+            );
+        }
+        Ok(())
+    }
+
+    /// Resizes the tensor at the given index, reallocating its buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if there is no tensor at `index` or TensorFlow Lite C fails internally.
+    pub fn resize_tensor(&mut self, index: usize, shape: &Shape) -> Result<()> {
+        let tensor_ptr = self.tensor_ptr(index)?;
+        let dimensions = shape.dimensions();
+        unsafe {
+            // TensorFlow Lite takes the ownership of `new_size`.
+            let new_size = TfLiteIntArrayCreate(dimensions.len() as c_int);
+            if new_size.is_null() {
+                return Err(Error::new(ErrorKind::FailedToResizeTensor(index)));
+            }
+            let data = (*new_size).data.as_mut_slice(dimensions.len());
+            for (dst, &dimension) in data.iter_mut().zip(dimensions.iter()) {
+                *dst = dimension as c_int;
+            }
+            if TfLiteOpaqueContextResizeTensor(self.context_ptr, tensor_ptr, new_size)
+                != TfLiteStatus_kTfLiteOk
+            {
+                return Err(Error::new(ErrorKind::FailedToResizeTensor(index)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the node at the given index of the graph.
+    fn node(&self, index: c_int) -> Option<Node<'_>> {
+        unsafe {
+            let mut node_ptr = std::ptr::null_mut();
+            let mut operator_ptr = std::ptr::null_mut();
+            let status = TfLiteOpaqueContextGetNodeAndRegistration(
+                self.context_ptr,
+                index,
+                &mut node_ptr,
+                &mut operator_ptr,
+            );
+            if status != TfLiteStatus_kTfLiteOk || node_ptr.is_null() || operator_ptr.is_null() {
+                return None;
+            }
+            let custom_name_ptr = TfLiteOperatorGetCustomName(operator_ptr);
+            let custom_name = if custom_name_ptr.is_null() {
+                None
+            } else {
+                Some(
+                    CStr::from_ptr(custom_name_ptr)
+                        .to_string_lossy()
+                        .into_owned(),
+                )
+            };
+            let mut inputs = (std::ptr::null(), 0);
+            TfLiteOpaqueNodeInputs(node_ptr, &mut inputs.0, &mut inputs.1);
+            let mut outputs = (std::ptr::null(), 0);
+            TfLiteOpaqueNodeOutputs(node_ptr, &mut outputs.0, &mut outputs.1);
+            Some(Node {
+                index: index as usize,
+                builtin_code: TfLiteOperatorGetBuiltInCode(operator_ptr) as i32,
+                custom_name,
+                inputs: tensor_indices(inputs.0, inputs.1),
+                outputs: tensor_indices(outputs.0, outputs.1)
+                    .into_iter()
+                    .flatten()
+                    .collect(),
+                builtin_data: TfLiteOpaqueNodeGetBuiltinData(node_ptr),
+                phantom: PhantomData,
+            })
+        }
+    }
+
+    /// Reports the given error to the error reporter of the interpreter.
+    fn report_error(&self, error: &Error) {
+        if let Ok(message) = CString::new(error.to_string()) {
+            unsafe {
+                TfLiteOpaqueContextReportError(
+                    self.context_ptr,
+                    b"%s\0".as_ptr() as *const c_char,
+                    message.as_ptr(),
+                );
+            }
+        }
+    }
+}
+
+/// Returns the tensor indices of the given C array, [`None`] for omitted optional tensors.
+unsafe fn tensor_indices(indices: *const c_int, count: c_int) -> Vec<Option<usize>> {
+    if indices.is_null() || count <= 0 {
+        return Vec::new();
+    }
+    std::slice::from_raw_parts(indices, count as usize)
+        .iter()
+        .map(|&index| {
+            if index < 0 {
+                None
+            } else {
+                Some(index as usize)
+            }
+        })
+        .collect()
+}
+
+/// Returns the elements of the given `TfLiteIntArray`.
+unsafe fn int_array<'a>(array: *const TfLiteIntArray) -> &'a [c_int] {
+    if array.is_null() || (*array).size <= 0 {
+        &[]
+    } else {
+        (*array).data.as_slice((*array).size as usize)
+    }
+}
+
+/// A node of the graph a [`Delegate`] is applied to.
+pub struct Node<'a> {
+    index: usize,
+    builtin_code: i32,
+    custom_name: Option<String>,
+    inputs: Vec<Option<usize>>,
+    outputs: Vec<usize>,
+    builtin_data: *mut c_void,
+    phantom: PhantomData<&'a ()>,
+}
+
+impl Debug for Node<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Node")
+            .field("index", &self.index)
+            .field("builtin_code", &self.builtin_code)
+            .field("custom_name", &self.custom_name)
+            .field("inputs", &self.inputs)
+            .field("outputs", &self.outputs)
+            .finish()
+    }
+}
+
+impl Node<'_> {
+    /// Returns the index of the node in the graph.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the `BuiltinOperator` code of the operator of the node, as defined in
+    /// the TensorFlow Lite schema (e.g. `0` for `ADD`).
+    pub fn builtin_code(&self) -> i32 {
+        self.builtin_code
+    }
+
+    /// Returns the name of the custom operator of the node, if any.
+    pub fn custom_name(&self) -> Option<&str> {
+        self.custom_name.as_deref()
+    }
+
+    /// Returns the indices of the input tensors, [`None`] for omitted optional inputs.
+    pub fn inputs(&self) -> &[Option<usize>] {
+        &self.inputs
+    }
+
+    /// Returns the indices of the output tensors.
+    pub fn outputs(&self) -> &[usize] {
+        &self.outputs
+    }
+
+    /// Returns the builtin options of the node, e.g. `TfLiteAddParams` for `ADD`.
+    ///
+    /// # Safety
+    ///
+    /// `T` must match the layout of the C struct of the builtin options of the operator,
+    /// see `tensorflow/lite/core/c/builtin_op_data.h`.
+    pub unsafe fn builtin_data<T>(&self) -> Option<&T> {
+        (self.builtin_data as *const T).as_ref()
+    }
+}
+
+/// A group of nodes replaced by a single [`DelegateKernel`].
+#[derive(Debug)]
+pub struct Partition<'a> {
+    nodes: Vec<Node<'a>>,
+    inputs: Vec<usize>,
+    outputs: Vec<usize>,
+}
+
+impl Partition<'_> {
+    /// Returns the nodes of the partition in execution order.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Returns the indices of the input tensors of the partition.
+    pub fn inputs(&self) -> &[usize] {
+        &self.inputs
+    }
+
+    /// Returns the indices of the output tensors of the partition.
+    pub fn outputs(&self) -> &[usize] {
+        &self.outputs
+    }
+}
+
+/// A [`DelegateFactory`] of a [`Delegate`] implemented in Rust.
+///
+/// Delegates are created with the opaque delegate builder of TensorFlow Lite C API.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use tflitec::delegate::{ArithmeticDelegate, OpaqueDelegateFactory};
+/// use tflitec::interpreter::{Interpreter, Options};
+/// use tflitec::model::Model;
+/// let options = Options::builder()
+///     .add_delegate(Arc::new(OpaqueDelegateFactory::new(ArithmeticDelegate)))
+///     .build()?;
+/// let model = Model::new("tests/add.bin")?;
+/// let interpreter = Interpreter::new(&model, Some(options))?;
+/// assert!(interpreter.delegation_report().delegated_node_count() > 0);
+/// # Ok::<(), tflitec::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct OpaqueDelegateFactory<D: Delegate> {
+    delegate: Arc<D>,
+}
+
+impl<D: Delegate + 'static> OpaqueDelegateFactory<D> {
+    /// Creates a new factory of the given `delegate`.
+    pub fn new(delegate: D) -> OpaqueDelegateFactory<D> {
+        OpaqueDelegateFactory {
+            delegate: Arc::new(delegate),
+        }
+    }
+
+    /// Returns the underlying delegate.
+    pub fn delegate(&self) -> &D {
+        &self.delegate
+    }
+}
+
+/// The data of a delegate created by an [`OpaqueDelegateFactory`].
+struct OpaqueDelegateData<D> {
+    delegate: Arc<D>,
+
+    /// The name of the delegate kernels, which must outlive them.
+    name: CString,
+}
+
+impl<D: Delegate + 'static> DelegateFactory for OpaqueDelegateFactory<D> {
+    fn name(&self) -> &str {
+        self.delegate.name()
+    }
+
+    fn create_delegate(&self) -> Result<RawDelegate> {
+        let name = CString::new(self.delegate.name())
+            .map_err(|_| Error::new(ErrorKind::FailedToCreateDelegate))?;
+        let data = Box::into_raw(Box::new(OpaqueDelegateData {
+            delegate: self.delegate.clone(),
+            name,
+        }));
+        let builder = TfLiteOpaqueDelegateBuilder {
+            data: data as *mut c_void,
+            Prepare: Some(prepare_opaque_delegate::<D>),
+            CopyFromBufferHandle: None,
+            CopyToBufferHandle: None,
+            FreeBufferHandle: None,
+            flags: TfLiteDelegateFlags_kTfLiteDelegateFlagsNone as i64,
+        };
+        unsafe {
+            let delegate_ptr = TfLiteOpaqueDelegateCreate(&builder);
+            if delegate_ptr.is_null() {
+                drop(Box::from_raw(data));
+                return Err(Error::new(ErrorKind::FailedToCreateDelegate));
+            }
+            let data = data as usize;
+            Ok(RawDelegate::from_raw(
+                delegate_ptr as *mut c_void,
+                move |delegate_ptr| {
+                    TfLiteOpaqueDelegateDelete(delegate_ptr as *mut TfLiteOpaqueDelegate);
+                    drop(Box::from_raw(data as *mut OpaqueDelegateData<D>));
+                },
+            ))
+        }
+    }
+}
+
+unsafe extern "C" fn prepare_opaque_delegate<D: Delegate>(
+    context: *mut TfLiteOpaqueContext,
+    delegate: *mut TfLiteOpaqueDelegate,
+    data: *mut c_void,
+) -> TfLiteStatus {
+    let data = &*(data as *const OpaqueDelegateData<D>);
+    let delegate_context = DelegateContext::from_raw(context);
+    let mut execution_plan = std::ptr::null_mut();
+    if TfLiteOpaqueContextGetExecutionPlan(context, &mut execution_plan) != TfLiteStatus_kTfLiteOk {
+        return TfLiteStatus_kTfLiteError;
+    }
+    // A `TfLiteIntArray` of the supported nodes, its first element is the size.
+    let mut supported_nodes: Vec<c_int> = vec![0];
+    let is_checked = crate::catch_unwind(false, || {
+        for &node_index in int_array(execution_plan) {
+            if let Some(node) = delegate_context.node(node_index) {
+                if data.delegate.is_node_supported(&delegate_context, &node) {
+                    supported_nodes.push(node_index);
+                }
+            }
+        }
+        true
+    });
+    if !is_checked {
+        return TfLiteStatus_kTfLiteError;
+    }
+    let node_count = supported_nodes.len() - 1;
+    supported_nodes[0] = node_count as c_int;
+
+    if node_count == 0 {
+        return TfLiteStatus_kTfLiteOk;
+    }
+    // TensorFlow Lite takes the ownership of the operator.
+    let operator = TfLiteOperatorCreate(
+        TfLiteBuiltinOperator_kTfLiteBuiltinDelegate,
+        data.name.as_ptr(),
+        1,
+        std::ptr::null_mut(),
+    );
+    TfLiteOperatorSetInit(operator, Some(init_kernel::<D>));
+    TfLiteOperatorSetFree(operator, Some(free_kernel));
+    TfLiteOperatorSetPrepare(operator, Some(prepare_kernel));
+    TfLiteOperatorSetInvoke(operator, Some(invoke_kernel));
+    TfLiteOpaqueContextReplaceNodeSubsetsWithDelegateKernels(
+        context,
+        operator,
+        supported_nodes.as_ptr() as *const TfLiteIntArray,
+        delegate,
+    )
+}
+
+unsafe extern "C" fn init_kernel<D: Delegate>(
+    context: *mut TfLiteOpaqueContext,
+    buffer: *const c_char,
+    _length: usize,
+) -> *mut c_void {
+    // Kernels of delegates are initialized with their `TfLiteOpaqueDelegateParams`.
+    let params = &*(buffer as *const TfLiteOpaqueDelegateParams);
+    let data = &*(TfLiteOpaqueDelegateGetData(params.delegate) as *const OpaqueDelegateData<D>);
+    let delegate_context = DelegateContext::from_raw(context);
+    let partition = Partition {
+        nodes: int_array(params.nodes_to_replace)
+            .iter()
+            .filter_map(|&index| delegate_context.node(index))
+            .collect(),
+        inputs: int_array(params.input_tensors)
+            .iter()
+            .map(|&index| index as usize)
+            .collect(),
+        outputs: int_array(params.output_tensors)
+            .iter()
+            .map(|&index| index as usize)
+            .collect(),
+    };
+    crate::catch_unwind(std::ptr::null_mut(), || {
+        match data.delegate.create_kernel(&delegate_context, &partition) {
+            Ok(kernel) => Box::into_raw(Box::new(kernel)) as *mut c_void,
+            Err(error) => {
+                delegate_context.report_error(&error);
+                std::ptr::null_mut()
+            }
+        }
+    })
+}
+
+unsafe extern "C" fn free_kernel(_context: *mut TfLiteOpaqueContext, data: *mut c_void) {
+    if !data.is_null() {
+        drop(Box::from_raw(data as *mut Box<dyn DelegateKernel>));
+    }
+}
+
+unsafe fn run_kernel<F>(
+    context: *mut TfLiteOpaqueContext,
+    node: *mut TfLiteOpaqueNode,
+    f: F,
+) -> TfLiteStatus
+where
+    F: FnOnce(&mut dyn DelegateKernel, &mut DelegateContext) -> Result<()>,
+{
+    let mut delegate_context = DelegateContext::from_raw(context);
+    let kernel = TfLiteOpaqueNodeGetUserData(node) as *mut Box<dyn DelegateKernel>;
+    if kernel.is_null() {
+        return TfLiteStatus_kTfLiteError;
+    }
+    crate::catch_unwind(TfLiteStatus_kTfLiteError, || {
+        match f((*kernel).as_mut(), &mut delegate_context) {
+            Ok(()) => TfLiteStatus_kTfLiteOk,
+            Err(error) => {
+                delegate_context.report_error(&error);
+                TfLiteStatus_kTfLiteError
+            }
+        }
+    })
+}
+
+unsafe extern "C" fn prepare_kernel(
+    context: *mut TfLiteOpaqueContext,
+    node: *mut TfLiteOpaqueNode,
+) -> TfLiteStatus {
+    run_kernel(context, node, |kernel, context| kernel.prepare(context))
+}
+
+unsafe extern "C" fn invoke_kernel(
+    context: *mut TfLiteOpaqueContext,
+    node: *mut TfLiteOpaqueNode,
+) -> TfLiteStatus {
+    run_kernel(context, node, |kernel, context| kernel.eval(context))
+}
+
+/// A reference [`Delegate`] running `float32` `ADD` and `MUL` operators of equally shaped
+/// tensors with Rust kernels.
+///
+/// It is mainly meant as an example of implementing a [`Delegate`] and is not optimized.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct ArithmeticDelegate;
+
+/// The leading fields of `TfLiteAddParams` and `TfLiteMulParams`.
+#[repr(C)]
+struct ArithmeticParams {
+    /// The `TfLiteFusedActivation` of the operator.
+    activation: c_int,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ArithmeticOperator {
+    Add,
+    Mul,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum FusedActivation {
+    None,
+    Relu,
+    ReluN1To1,
+    Relu6,
+}
+
+impl FusedActivation {
+    fn new(activation: c_int) -> Option<FusedActivation> {
+        // See `TfLiteFusedActivation` in `tensorflow/lite/core/c/builtin_op_data.h`.
+        match activation {
+            0 => Some(FusedActivation::None),
+            1 => Some(FusedActivation::Relu),
+            2 => Some(FusedActivation::ReluN1To1),
+            3 => Some(FusedActivation::Relu6),
+            _ => None,
+        }
+    }
+
+    fn apply(self, value: f32) -> f32 {
+        match self {
+            FusedActivation::None => value,
+            FusedActivation::Relu => value.max(0.0),
+            FusedActivation::ReluN1To1 => value.clamp(-1.0, 1.0),
+            FusedActivation::Relu6 => value.clamp(0.0, 6.0),
+        }
+    }
+}
+
+/// A single node run by an [`ArithmeticKernel`].
+#[derive(Debug)]
+struct ArithmeticNode {
+    operator: ArithmeticOperator,
+    activation: FusedActivation,
+    inputs: [usize; 2],
+    output: usize,
+}
+
+impl ArithmeticDelegate {
+    fn arithmetic_node(node: &Node) -> Option<ArithmeticNode> {
+        let operator = match node.builtin_code() as TfLiteBuiltinOperator {
+            code if code == TfLiteBuiltinOperator_kTfLiteBuiltinAdd => ArithmeticOperator::Add,
+            code if code == TfLiteBuiltinOperator_kTfLiteBuiltinMul => ArithmeticOperator::Mul,
+            _ => return None,
+        };
+        let activation = match unsafe { node.builtin_data::<ArithmeticParams>() } {
+            Some(params) => FusedActivation::new(params.activation)?,
+            None => FusedActivation::None,
+        };
+        match (node.inputs(), node.outputs()) {
+            ([Some(lhs), Some(rhs)], [output]) => Some(ArithmeticNode {
+                operator,
+                activation,
+                inputs: [*lhs, *rhs],
+                output: *output,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl Delegate for ArithmeticDelegate {
+    fn name(&self) -> &str {
+        "ArithmeticDelegate"
+    }
+
+    fn is_node_supported(&self, context: &DelegateContext, node: &Node) -> bool {
+        let node = match ArithmeticDelegate::arithmetic_node(node) {
+            Some(node) => node,
+            None => return false,
+        };
+        let tensors = [node.inputs[0], node.inputs[1], node.output];
+        let is_float32 = tensors
+            .iter()
+            .all(|&index| matches!(context.tensor_data_type(index), Ok(DataType::Float32)));
+        let shapes = tensors
+            .iter()
+            .map(|&index| context.tensor_shape(index).ok())
+            .collect::<Vec<_>>();
+        is_float32 && shapes[0].is_some() && shapes.iter().all(|shape| *shape == shapes[0])
+    }
+
+    fn create_kernel(
+        &self,
+        _context: &DelegateContext,
+        partition: &Partition,
+    ) -> Result<Box<dyn DelegateKernel>> {
+        let nodes = partition
+            .nodes()
+            .iter()
+            .map(ArithmeticDelegate::arithmetic_node)
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| Error::new(ErrorKind::FailedToCreateDelegate))?;
+        Ok(Box::new(ArithmeticKernel {
+            nodes,
+            buffer: Vec::new(),
+        }))
+    }
+}
+
+/// The [`DelegateKernel`] of [`ArithmeticDelegate`].
+#[derive(Debug)]
+struct ArithmeticKernel {
+    nodes: Vec<ArithmeticNode>,
+
+    /// The buffer of the output of the current node.
+    buffer: Vec<f32>,
+}
+
+impl DelegateKernel for ArithmeticKernel {
+    fn prepare(&mut self, context: &mut DelegateContext) -> Result<()> {
+        for node in self.nodes.iter() {
+            let shape = context.tensor_shape(node.inputs[0])?;
+            if context.tensor_shape(node.inputs[1])? != shape {
+                return Err(Error::new(ErrorKind::FailedToPrepareDelegateKernel));
+            }
+            if context.tensor_shape(node.output)? != shape {
+                context.resize_tensor(node.output, &shape)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn eval(&mut self, context: &mut DelegateContext) -> Result<()> {
+        for node in self.nodes.iter() {
+            let lhs = context.tensor_data::<f32>(node.inputs[0])?;
+            let rhs = context.tensor_data::<f32>(node.inputs[1])?;
+            self.buffer.clear();
+            self.buffer
+                .extend(lhs.iter().zip(rhs.iter()).map(|(&lhs, &rhs)| {
+                    let value = match node.operator {
+                        ArithmeticOperator::Add => lhs + rhs,
+                        ArithmeticOperator::Mul => lhs * rhs,
+                    };
+                    node.activation.apply(value)
+                }));
+            context.set_tensor_data(node.output, &self.buffer)?;
+        }
+        Ok(())
+    }
+}
+
+/// A report of the delegates applied to an [`Interpreter`][crate::interpreter::Interpreter].
+///
+/// See [`Interpreter::delegation_report()`][crate::interpreter::Interpreter::delegation_report].
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct DelegationReport {
+    /// The reports of the delegates, in the order they are applied.
+    delegates: Vec<DelegateReport>,
+
+    /// Indicates whether a delegate failed and the interpreter fell back to the CPU kernels.
+    is_fallback: bool,
+}
+
+impl DelegationReport {
+    /// Returns the reports of the delegates, in the order they are applied.
+    pub fn delegates(&self) -> &[DelegateReport] {
+        &self.delegates
+    }
+
+    /// Returns whether a delegate failed to be applied and the interpreter fell back to
+    /// the CPU kernels.
+    ///
+    /// - Note: TensorFlow Lite undoes *all* delegates when falling back, including the ones
+    /// which were applied successfully.
+    pub fn is_fallback(&self) -> bool {
+        self.is_fallback
+    }
+
+    /// Returns the total number of nodes claimed by the applied delegates.
+    pub fn delegated_node_count(&self) -> usize {
+        self.delegates
+            .iter()
+            .filter(|delegate| delegate.is_applied)
+            .filter_map(|delegate| delegate.node_count)
+            .sum()
+    }
+}
+
+/// A report of a single delegate, see [`DelegationReport`].
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct DelegateReport {
+    /// The name of the delegate.
+    pub name: String,
+
+    /// Indicates whether the delegate is in use by the interpreter.
+    pub is_applied: bool,
+
+    /// Indicates whether the delegate itself failed to be applied.
+    pub is_failed: bool,
+
+    /// The number of nodes of the model replaced by the kernels of the delegate when it was
+    /// applied, or [`None`] if the delegate or a delegate before it failed.
+    pub node_count: Option<usize>,
+}
+
+/// The nodes claimed by the delegates applied to an interpreter, found by inspecting its
+/// execution plan.
+#[derive(Debug, Default)]
+struct InspectionState {
+    /// The names of the inspected delegates, in the order they are applied.
+    names: Vec<String>,
+
+    /// The number of nodes of the model left in the execution plan at the last inspection, or
+    /// [`None`] before the first one.
+    model_node_count: Option<usize>,
+
+    /// The largest node index in the execution plan at the last inspection.
+    max_node_index: Option<c_int>,
+
+    /// Whether the execution plan was inspected after applying each delegate.
+    inspected: Vec<bool>,
+
+    /// The number of nodes of the model claimed by each delegate.
+    node_counts: Vec<usize>,
+
+    /// The names of the delegates, keyed by the names of their kernels.
+    kernel_delegates: HashMap<String, String>,
+}
+
+/// The data of a delegate inspecting the execution plan after the delegate at `position`.
+struct InspectorData {
+    position: usize,
+    state: Arc<Mutex<InspectionState>>,
+}
+
+/// Reports the nodes claimed by the delegates applied to an interpreter.
+///
+/// An inspecting delegate, which claims no nodes itself, is added right after each inspected
+/// delegate. When TensorFlow Lite prepares it, all delegates before it are applied, so the
+/// nodes claimed by the inspected delegate are the nodes of the model which left the execution
+/// plan since the previous inspection. An inspector following a failed delegate never runs.
+///
+/// Only the public graph of the execution plan is read, which relies on how TensorFlow Lite
+/// applies delegates:
+/// - The execution plan of the model lists all its nodes before any delegate is applied.
+/// - The kernel of a delegate is a new node with the `DELEGATE` builtin code, which is never
+///   used by the nodes of a model, and its index is larger than the indices of all the nodes
+///   before it.
+/// - The nodes claimed by a delegate, including the kernels of earlier delegates, are removed
+///   from the execution plan. Only the claimed nodes of the model are counted.
+pub(crate) struct DelegationInspector {
+    /// The inspecting delegates, which must outlive the interpreter.
+    inspectors: Vec<RawDelegate>,
+
+    state: Arc<Mutex<InspectionState>>,
+}
+
+impl Debug for DelegationInspector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DelegationInspector")
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl DelegationInspector {
+    /// Creates an inspector of the delegates with the given `names`, in the order they are
+    /// applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if an inspecting delegate cannot be created.
+    pub(crate) fn new(names: Vec<String>) -> Result<DelegationInspector> {
+        let count = names.len();
+        let state = Arc::new(Mutex::new(InspectionState {
+            names,
+            model_node_count: None,
+            max_node_index: None,
+            inspected: vec![false; count],
+            node_counts: vec![0; count],
+            kernel_delegates: HashMap::new(),
+        }));
+        let inspectors = (0..count)
+            .map(|position| {
+                let data = Box::into_raw(Box::new(InspectorData {
+                    position,
+                    state: state.clone(),
+                }));
+                let builder = TfLiteOpaqueDelegateBuilder {
+                    data: data as *mut c_void,
+                    Prepare: Some(prepare_inspector),
+                    CopyFromBufferHandle: None,
+                    CopyToBufferHandle: None,
+                    FreeBufferHandle: None,
+                    flags: TfLiteDelegateFlags_kTfLiteDelegateFlagsAllowDynamicTensors as i64,
+                };
+                unsafe {
+                    let delegate_ptr = TfLiteOpaqueDelegateCreate(&builder);
+                    if delegate_ptr.is_null() {
+                        drop(Box::from_raw(data));
+                        return Err(Error::new(ErrorKind::FailedToCreateDelegate));
+                    }
+                    let data = data as usize;
+                    Ok(RawDelegate::from_raw(
+                        delegate_ptr as *mut c_void,
+                        move |delegate_ptr| {
+                            TfLiteOpaqueDelegateDelete(delegate_ptr as *mut TfLiteOpaqueDelegate);
+                            drop(Box::from_raw(data as *mut InspectorData));
+                        },
+                    ))
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(DelegationInspector { inspectors, state })
+    }
+
+    /// Returns the number of inspected delegates.
+    pub(crate) fn len(&self) -> usize {
+        self.inspectors.len()
+    }
+
+    /// Returns the inspecting delegate to add right after the inspected delegate at `position`.
+    pub(crate) fn inspect(&self, position: usize) -> *mut TfLiteOpaqueDelegate {
+        self.inspectors[position].as_ptr() as *mut TfLiteOpaqueDelegate
+    }
+
+    /// Returns the delegates of kernels, which are known once the interpreter is created.
+    pub(crate) fn kernel_delegates(&self) -> KernelDelegates {
+        KernelDelegates(self.state.clone())
+    }
+
+    /// Returns the report of the delegates after the interpreter is created.
+    pub(crate) fn report(&self) -> DelegationReport {
+        let state = self.state.lock().unwrap();
+        let failed_position = state.inspected.iter().position(|inspected| !inspected);
+        let is_fallback = failed_position.is_some();
+        let delegates = state
+            .names
+            .iter()
+            .enumerate()
+            .map(|(position, name)| DelegateReport {
+                name: name.clone(),
+                is_applied: !is_fallback,
+                is_failed: failed_position == Some(position),
+                node_count: if state.inspected[position] {
+                    Some(state.node_counts[position])
+                } else {
+                    None
+                },
+            })
+            .collect();
+        DelegationReport {
+            delegates,
+            is_fallback,
+        }
+    }
+}
+
+/// The delegates of the kernels in the execution plan of an interpreter, see
+/// [`DelegationInspector::kernel_delegates()`].
+#[derive(Clone, Debug)]
+pub(crate) struct KernelDelegates(Arc<Mutex<InspectionState>>);
+
+impl KernelDelegates {
+    /// Returns the name of the delegate of the kernel with the given name, or [`None`] if it is
+    /// not a delegate kernel.
+    pub(crate) fn delegate(&self, kernel_name: &str) -> Option<String> {
+        self.0
+            .lock()
+            .unwrap()
+            .kernel_delegates
+            .get(kernel_name)
+            .cloned()
+    }
+}
+
+unsafe extern "C" fn prepare_inspector(
+    context: *mut TfLiteOpaqueContext,
+    _delegate: *mut TfLiteOpaqueDelegate,
+    data: *mut c_void,
+) -> TfLiteStatus {
+    let data = &*(data as *const InspectorData);
+    let mut execution_plan = std::ptr::null_mut();
+    if TfLiteOpaqueContextGetExecutionPlan(context, &mut execution_plan) != TfLiteStatus_kTfLiteOk {
+        return TfLiteStatus_kTfLiteError;
+    }
+    let execution_plan = int_array(execution_plan);
+    let mut state = data.state.lock().unwrap();
+    let mut model_node_count = 0;
+    let mut kernel_names = Vec::new();
+    let mut first_kernel_index = None;
+    for &node_index in execution_plan {
+        let mut node_ptr = std::ptr::null_mut();
+        let mut operator_ptr = std::ptr::null_mut();
+        let status = TfLiteOpaqueContextGetNodeAndRegistration(
+            context,
+            node_index,
+            &mut node_ptr,
+            &mut operator_ptr,
+        );
+        if status != TfLiteStatus_kTfLiteOk || operator_ptr.is_null() {
+            continue;
+        }
+        if TfLiteOperatorGetBuiltInCode(operator_ptr)
+            != TfLiteBuiltinOperator_kTfLiteBuiltinDelegate
+        {
+            model_node_count += 1;
+            continue;
+        }
+        first_kernel_index = Some(first_kernel_index.unwrap_or(node_index).min(node_index));
+        // Kernels added since the last inspection belong to the inspected delegate.
+        if !matches!(state.max_node_index, Some(max) if node_index <= max) {
+            let kernel_name = TfLiteOperatorGetCustomName(operator_ptr);
+            if !kernel_name.is_null() {
+                kernel_names.push(CStr::from_ptr(kernel_name).to_string_lossy().into_owned());
+            }
+        }
+    }
+    // At the first inspection, the kernels of the inspected delegate follow all the nodes of
+    // the model, or the execution plan still lists all of them.
+    let previous_model_node_count = state
+        .model_node_count
+        .unwrap_or_else(|| first_kernel_index.map_or(model_node_count, |index| index as usize));
+    state.node_counts[data.position] = previous_model_node_count.saturating_sub(model_node_count);
+    state.model_node_count = Some(model_node_count);
+    state.max_node_index = execution_plan
+        .iter()
+        .copied()
+        .max()
+        .or(state.max_node_index);
+    let delegate_name = state.names[data.position].clone();
+    for kernel_name in kernel_names {
+        state
+            .kernel_delegates
+            .insert(kernel_name, delegate_name.clone());
+    }
+    state.inspected[data.position] = true;
+    TfLiteStatus_kTfLiteOk
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::delegate::{
+        ArithmeticDelegate, Delegate, DelegateContext, DelegateKernel, Node, OpaqueDelegateFactory,
+        Partition,
+    };
+    use crate::interpreter::{Interpreter, Options};
+    use crate::model::Model;
+    use crate::Result;
+    use std::sync::Arc;
+
+    const MODEL_PATH: &str = "tests/add.bin";
+
+    #[test]
+    fn test_arithmetic_delegate() {
+        let options = Options::builder()
+            .add_delegate(Arc::new(OpaqueDelegateFactory::new(ArithmeticDelegate)))
+            .build()
+            .expect("Cannot build options");
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let interpreter =
+            Interpreter::new(&model, Some(options)).expect("Cannot create interpreter!");
+        let report = interpreter.delegation_report();
+        assert!(!report.is_fallback());
+        assert_eq!(report.delegates().len(), 1);
+        let delegate = &report.delegates()[0];
+        assert_eq!(delegate.name, "ArithmeticDelegate");
+        assert!(delegate.is_applied);
+        // Both ADD nodes of the model are claimed.
+        assert_eq!(delegate.node_count, Some(2));
+
+        interpreter
+            .allocate_tensors()
+            .expect("Cannot allocate tensors");
+        let data = (0..192).map(|x| x as f32).collect::<Vec<f32>>();
+        assert!(interpreter.copy(&data[..], 0).is_ok());
+        assert!(interpreter.invoke().is_ok());
+        let expected: Vec<f32> = data.iter().map(|e| e * 3.0).collect();
+        let output_tensor = interpreter.output(0).unwrap();
+        assert_eq!(expected, output_tensor.data::<f32>());
+    }
+
+    /// A delegate claiming all nodes whose kernels panic when evaluated.
+    struct PanickingDelegate;
+
+    struct PanickingKernel;
+
+    impl Delegate for PanickingDelegate {
+        fn name(&self) -> &str {
+            "PanickingDelegate"
+        }
+
+        fn is_node_supported(&self, _context: &DelegateContext, _node: &Node) -> bool {
+            true
+        }
+
+        fn create_kernel(
+            &self,
+            _context: &DelegateContext,
+            _partition: &Partition,
+        ) -> Result<Box<dyn DelegateKernel>> {
+            Ok(Box::new(PanickingKernel))
+        }
+    }
+
+    impl DelegateKernel for PanickingKernel {
+        fn eval(&mut self, _context: &mut DelegateContext) -> Result<()> {
+            panic!("kernel panicked")
+        }
+    }
+
+    #[test]
+    fn test_panicking_delegate() {
+        let options = Options::builder()
+            .add_delegate(Arc::new(OpaqueDelegateFactory::new(PanickingDelegate)))
+            .build()
+            .expect("Cannot build options");
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let interpreter =
+            Interpreter::new(&model, Some(options)).expect("Cannot create interpreter!");
+        interpreter
+            .allocate_tensors()
+            .expect("Cannot allocate tensors");
+        assert!(interpreter.invoke().is_err());
+    }
+
+    #[cfg(all(unix, feature = "external_delegate"))]
+    const STUB_DELEGATE_SOURCE: &str = "tests/stub_delegate.c";
+
+    /// Compiles the stub delegate library with the C compiler of the system.
+    #[cfg(all(unix, feature = "external_delegate"))]
+    fn build_stub_delegate() -> std::path::PathBuf {
+        let library_path = std::env::temp_dir().join(format!(
+            "libtflitec_stub_delegate_{}.so",
+            std::process::id()
+        ));
+        let status =
+            std::process::Command::new(std::env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+                .args(["-shared", "-fPIC", "-o"])
+                .arg(&library_path)
+                .arg(STUB_DELEGATE_SOURCE)
+                .status()
+                .expect("Cannot run C compiler");
+        assert!(status.success(), "Cannot compile stub delegate");
+        library_path
+    }
+
+    #[test]
+    #[cfg(all(unix, feature = "external_delegate"))]
+    fn test_external_delegate() {
+        use crate::delegate::ExternalDelegate;
+        use crate::ErrorKind;
+        use std::os::raw::c_int;
+        use std::sync::Mutex;
+
+        let library_path = build_stub_delegate();
+        let delegate = unsafe { ExternalDelegate::new(&library_path) }
+            .expect("Cannot load stub delegate")
+            .option("fail", "false");
+        let library = delegate.library.clone();
+        let live_count = unsafe {
+            library
+                .get::<unsafe extern "C" fn() -> c_int>(b"stub_delegate_live_count\0")
+                .expect("Cannot find stub_delegate_live_count")
+        };
+        let failing_delegate = delegate.clone().option("fail", "true");
+        let failing_prepare_delegate = delegate.clone().option("fail_prepare", "true");
+        assert_eq!(failing_delegate.options().len(), 2);
+
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let options = Options::builder()
+            .add_delegate(Arc::new(delegate))
+            .build()
+            .expect("Cannot build options");
+        let interpreter =
+            Interpreter::new(&model, Some(options)).expect("Cannot create interpreter!");
+        assert_eq!(unsafe { live_count() }, 1);
+        let report = interpreter.delegation_report();
+        assert_eq!(report.delegates().len(), 1);
+        assert!(report.delegates()[0].is_applied);
+        assert_eq!(report.delegates()[0].node_count, Some(0));
+        interpreter
+            .allocate_tensors()
+            .expect("Cannot allocate tensors");
+        let data = (0..192).map(|x| x as f32).collect::<Vec<f32>>();
+        assert!(interpreter.copy(&data[..], 0).is_ok());
+        assert!(interpreter.invoke().is_ok());
+        drop(interpreter);
+        assert_eq!(unsafe { live_count() }, 0);
+
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let reported_messages = messages.clone();
+        let options = Options::builder()
+            .add_delegate(Arc::new(failing_delegate))
+            .error_reporter(move |message| {
+                reported_messages.lock().unwrap().push(message.to_string())
+            })
+            .build()
+            .expect("Cannot build options");
+        let err = Interpreter::new(&model, Some(options)).err().unwrap();
+        assert_eq!(ErrorKind::FailedToCreateDelegate, err.kind());
+        assert_eq!(
+            messages.lock().unwrap().as_slice(),
+            ["stub delegate failed on request"]
+        );
+
+        let options = Options::builder()
+            .add_delegate(Arc::new(OpaqueDelegateFactory::new(ArithmeticDelegate)))
+            .add_delegate(Arc::new(failing_prepare_delegate))
+            .enable_delegate_fallback(true)
+            .build()
+            .expect("Cannot build options");
+        let interpreter =
+            Interpreter::new(&model, Some(options)).expect("Cannot create interpreter!");
+        let report = interpreter.delegation_report();
+        assert!(report.is_fallback());
+        assert_eq!(report.delegated_node_count(), 0);
+        let delegates = report.delegates();
+        assert!(!delegates[0].is_applied && !delegates[0].is_failed);
+        assert!(delegates[0].node_count.unwrap() > 0);
+        assert!(!delegates[1].is_applied && delegates[1].is_failed);
+        assert_eq!(delegates[1].node_count, None);
+        drop(interpreter);
+        assert_eq!(unsafe { live_count() }, 0);
+
+        let err = unsafe { ExternalDelegate::new(STUB_DELEGATE_SOURCE) }
+            .err()
+            .unwrap();
+        assert_eq!(ErrorKind::FailedToLoadDelegateLibrary, err.kind());
+        std::fs::remove_file(library_path).ok();
+    }
+}
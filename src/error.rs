@@ -1,5 +1,8 @@
 //! Definitions of `Error` type and `ErrorKind`s of the crate.
 use core::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use crate::bindings::TfLiteBuiltinOperator_kTfLiteBuiltinCustom;
 
 /// A list specifying general categories of TensorFlow Lite errors.
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -8,16 +11,73 @@ pub enum ErrorKind {
     InvalidTensorIndex(/* index: */ usize, /* max_index: */ usize),
     /// Indicates given data length (first value) is not equal to required length (second value).
     InvalidTensorDataCount(/* provided: */ usize, /* required: */ usize),
+    /// Indicates given count of inputs or input shapes (first value) is not equal to the count
+    /// of input tensors (second value).
+    InvalidInputCount(/* provided: */ usize, /* required: */ usize),
     /// Indicates failure to resize tensor with index (first value).
     FailedToResizeInputTensor(/* index: */ usize),
+    /// Indicates failure to resize tensor with index (first value) in a delegate kernel.
+    FailedToResizeTensor(/* index: */ usize),
+    /// Indicates failure to set custom allocation of input or output tensor with index
+    /// (first value).
+    FailedToSetCustomAllocation(/* index: */ usize),
+    /// Indicates the buffer bound to the tensor with index (first value) among all tensors was
+    /// released by a dropped [`CustomAllocations`][crate::interpreter::CustomAllocations]
+    /// guard, and no new buffer is bound to it.
+    CustomAllocationReleased(/* index: */ usize),
+    /// Indicates given buffer is not aligned to the required alignment (first value) in bytes.
+    MisalignedTensorBuffer(/* required_alignment: */ usize),
+    /// Indicates the model uses the builtin operator with code (first value) and version
+    /// (second value) which is not allowed by the
+    /// [`ModelValidator`][crate::validator::ModelValidator]. The name of a custom operator is
+    /// given by [`Error::requested_name()`].
+    DisallowedOperator(/* code: */ i32, /* version: */ i32),
+    /// Indicates given thread count (first value) is less than -1.
+    InvalidThreadCount(/* thread_count: */ i32),
+    /// Indicates given initial size (first value) of an
+    /// [`InterpreterPool`][crate::pool::InterpreterPool] is larger than its maximum size
+    /// (second value), or the maximum size is 0.
+    InvalidPoolSize(/* size: */ usize, /* max_size: */ usize),
+    /// Indicates the input tensor with index (first value) has a batch size (second value), i.e.
+    /// first dimension, different from the batch size of the first input (third value).
+    MismatchedBatchSize(
+        /* index: */ usize,
+        /* batch_size: */ usize,
+        /* required: */ usize,
+    ),
+    /// Indicates the output tensor with index (first value) has a batch size (second value),
+    /// i.e. first dimension, different from the batch size of the inputs (third value).
+    MismatchedOutputBatchSize(
+        /* index: */ usize,
+        /* batch_size: */ usize,
+        /* required: */ usize,
+    ),
+    /// Indicates no input or output tensor has the given name, see
+    /// [`Error::requested_name()`] and [`Error::available_names()`].
+    UnknownTensorName,
+    /// Indicates the model has no signature with the given key, see
+    /// [`Error::requested_name()`] and [`Error::available_names()`].
+    UnknownSignatureKey,
     AllocateTensorsRequired,
     InvalidTensorDataType,
     FailedToAllocateTensors,
+    FailedToResetVariableTensors,
     FailedToCopyDataToInputTensor,
     FailedToLoadModel,
     FailedToCreateInterpreter,
+    FailedToCreateSignatureRunner,
+    FailedToInvokeSignatureRunner,
+    FailedToCreateWeightCache,
+    FailedToFinalizeWeightCache,
+    UnknownOperator,
+    FailedToLoadDelegateLibrary,
+    FailedToCreateDelegate,
+    FailedToPrepareDelegateKernel,
     ReadTensorError,
     InvokeInterpreterRequired,
+    InvokeCancelled,
+    InferenceQueueFull,
+    InferenceWorkerStopped,
 }
 
 impl ErrorKind {
@@ -30,6 +90,10 @@ impl ErrorKind {
                 "provided data count {} must match the required count {}",
                 provided, required
             ),
+            ErrorKind::InvalidInputCount(provided, required) => format!(
+                "provided input count {} must match the input tensor count {}",
+                provided, required
+            ),
             ErrorKind::InvalidTensorDataType => {
                 "tensor data type is unsupported or could not be determined due to a model error"
                     .to_string()
@@ -37,17 +101,85 @@ impl ErrorKind {
             ErrorKind::FailedToResizeInputTensor(index) => {
                 format!("failed to resize input tensor at index {}", index)
             }
+            ErrorKind::FailedToResizeTensor(index) => {
+                format!("failed to resize tensor at index {}", index)
+            }
+            ErrorKind::FailedToSetCustomAllocation(index) => {
+                format!(
+                    "failed to set custom allocation of tensor at index {}",
+                    index
+                )
+            }
+            ErrorKind::CustomAllocationReleased(index) => format!(
+                "the custom allocation of tensor at index {} was released, bind a new buffer",
+                index
+            ),
+            ErrorKind::MisalignedTensorBuffer(alignment) => {
+                format!("tensor buffer must be aligned to {} bytes", alignment)
+            }
+            ErrorKind::DisallowedOperator(code, version) => {
+                let name = crate::validator::builtin_operator_name(code)
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("BUILTIN_{}", code));
+                format!(
+                    "operator {} (version {}) is not allowed by the model validator",
+                    name, version
+                )
+            }
+            ErrorKind::InvalidThreadCount(thread_count) => {
+                format!("invalid thread count {}, must be >= -1", thread_count)
+            }
+            ErrorKind::InvalidPoolSize(size, max_size) => format!(
+                "invalid pool size {}, must be at most max size {} which must be positive",
+                size, max_size
+            ),
+            ErrorKind::MismatchedBatchSize(index, batch_size, required) => format!(
+                "batch size {} of input tensor at index {} must match the batch size {} of the \
+                 first input",
+                batch_size, index, required
+            ),
+            ErrorKind::MismatchedOutputBatchSize(index, batch_size, required) => format!(
+                "batch size {} of output tensor at index {} must match the batch size {} of the \
+                 inputs",
+                batch_size, index, required
+            ),
+            ErrorKind::UnknownTensorName => "unknown tensor name".to_string(),
+            ErrorKind::UnknownSignatureKey => "unknown signature key".to_string(),
             ErrorKind::AllocateTensorsRequired => "must call allocate_tensors()".to_string(),
             ErrorKind::FailedToAllocateTensors => {
                 "failed to allocate memory for input tensors".to_string()
             }
+            ErrorKind::FailedToResetVariableTensors => {
+                "failed to reset variable tensors".to_string()
+            }
             ErrorKind::FailedToCopyDataToInputTensor => {
                 "failed to copy data to input tensor".to_string()
             }
             ErrorKind::FailedToLoadModel => "failed to load the given model".to_string(),
             ErrorKind::FailedToCreateInterpreter => "failed to create the interpreter".to_string(),
+            ErrorKind::FailedToCreateSignatureRunner => {
+                "failed to create the signature runner".to_string()
+            }
+            ErrorKind::FailedToInvokeSignatureRunner => {
+                "failed to invoke the signature runner".to_string()
+            }
+            ErrorKind::FailedToCreateWeightCache => "failed to create the weight cache".to_string(),
+            ErrorKind::FailedToFinalizeWeightCache => {
+                "failed to finalize the weight cache".to_string()
+            }
+            ErrorKind::UnknownOperator => "unknown builtin operator name".to_string(),
+            ErrorKind::FailedToLoadDelegateLibrary => {
+                "failed to load the delegate library".to_string()
+            }
+            ErrorKind::FailedToCreateDelegate => "failed to create the delegate".to_string(),
+            ErrorKind::FailedToPrepareDelegateKernel => {
+                "failed to prepare the delegate kernel".to_string()
+            }
             ErrorKind::ReadTensorError => "failed to read tensor".to_string(),
             ErrorKind::InvokeInterpreterRequired => "must call invoke()".to_string(),
+            ErrorKind::InvokeCancelled => "invoke() was cancelled".to_string(),
+            ErrorKind::InferenceQueueFull => "the inference queue is full".to_string(),
+            ErrorKind::InferenceWorkerStopped => "the inference workers stopped".to_string(),
         }
     }
 }
@@ -59,14 +191,36 @@ impl Display for ErrorKind {
 }
 
 /// The error type for TensorFlow Lite operations.
-#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+///
+/// - Note: `Error` is not `Copy` since it carries the names of
+/// [`Error::requested_name()`] and [`Error::available_names()`]. They are shared between
+/// clones, so cloning an `Error` is cheap, e.g. to send it to several requests of a batch.
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub struct Error {
     kind: ErrorKind,
+
+    /// The details of [`ErrorKind::UnknownTensorName`], [`ErrorKind::UnknownSignatureKey`] and
+    /// custom [`ErrorKind::DisallowedOperator`] errors, shared to keep `Error` small and cheap
+    /// to clone.
+    unknown_name: Option<Arc<UnknownName>>,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+struct UnknownName {
+    name: String,
+    available_names: Vec<String>,
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.kind)
+        match &self.unknown_name {
+            Some(unknown_name) => write!(
+                f,
+                "{} {:?}, available names are {:?}",
+                self.kind, unknown_name.name, unknown_name.available_names
+            ),
+            None => write!(f, "{}", self.kind),
+        }
     }
 }
 
@@ -74,11 +228,73 @@ impl std::error::Error for Error {}
 
 impl Error {
     pub(crate) fn new(kind: ErrorKind) -> Error {
-        Error { kind }
+        Error {
+            kind,
+            unknown_name: None,
+        }
     }
+
+    pub(crate) fn unknown_tensor_name(name: &str, available_names: Vec<String>) -> Error {
+        Error::unknown_name(ErrorKind::UnknownTensorName, name, available_names)
+    }
+
+    pub(crate) fn unknown_signature_key(key: &str, available_keys: Vec<String>) -> Error {
+        Error::unknown_name(ErrorKind::UnknownSignatureKey, key, available_keys)
+    }
+
+    pub(crate) fn disallowed_custom_operator(
+        name: &str,
+        version: i32,
+        allowed_names: Vec<String>,
+    ) -> Error {
+        Error::unknown_name(
+            ErrorKind::DisallowedOperator(
+                TfLiteBuiltinOperator_kTfLiteBuiltinCustom as i32,
+                version,
+            ),
+            name,
+            allowed_names,
+        )
+    }
+
+    fn unknown_name(kind: ErrorKind, name: &str, available_names: Vec<String>) -> Error {
+        Error {
+            kind,
+            unknown_name: Some(Arc::new(UnknownName {
+                name: name.to_string(),
+                available_names,
+            })),
+        }
+    }
+
     pub fn kind(&self) -> ErrorKind {
         self.kind
     }
+
+    /// Returns the requested name of an [`ErrorKind::UnknownTensorName`] or
+    /// [`ErrorKind::UnknownSignatureKey`] error, or the name of a disallowed custom operator of
+    /// an [`ErrorKind::DisallowedOperator`] error.
+    pub fn requested_name(&self) -> Option<&str> {
+        self.unknown_name
+            .as_ref()
+            .map(|unknown_name| unknown_name.name.as_str())
+    }
+
+    /// Returns the names which could have been requested instead, in order, for
+    /// [`ErrorKind::UnknownTensorName`] and [`ErrorKind::UnknownSignatureKey`] errors, the
+    /// allowed custom operators for [`ErrorKind::DisallowedOperator`] errors, or an empty slice
+    /// otherwise.
+    pub fn available_names(&self) -> &[String] {
+        self.unknown_name
+            .as_ref()
+            .map_or(&[], |unknown_name| unknown_name.available_names.as_slice())
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Error {
+        Error::new(kind)
+    }
 }
 
 /// A specialized [`Result`] type for API operations.
@@ -1,16 +1,273 @@
 //! API of TensorFlow Lite [`Interpreter`] that performs inference.
-use std::ffi::c_void;
-use std::os::raw::c_int;
+use std::collections::{HashMap, HashSet};
+use std::ffi::{c_void, CStr};
+use std::marker::PhantomData;
+use std::os::raw::{c_char, c_int};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use crate::bindings::*;
+use crate::delegate::{DelegateFactory, DelegationInspector, DelegationReport, RawDelegate};
+use crate::metrics::Metrics;
 use crate::model::Model;
+use crate::profiler::{self, TelemetryContext, TelemetryProfiler};
+use crate::signature::SignatureRunner;
 use crate::tensor;
 use crate::tensor::Tensor;
+use crate::thread_pool::ThreadPool;
+use crate::trace::{TraceRecorder, TraceSpan};
+use crate::validator::ModelValidator;
 use crate::{Error, ErrorKind, Result};
 use std::fmt::{Debug, Formatter};
 
+/// A function receiving error messages reported by TensorFlow Lite.
+///
+/// - Note: Panics of the function are caught and ignored, since they cannot unwind through
+/// TensorFlow Lite C.
+pub type ErrorReporter = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Options for configuring the XNNPACK delegate of the [`Interpreter`].
+///
+/// Each flag is explicitly set or cleared on the XNNPACK delegate. [`XnnpackOptions::default()`]
+/// returns the defaults of the TensorFlow Lite build, e.g. quantized operators are enabled by
+/// default if the crate is built with `xnnpack_qs8` or `xnnpack_qu8`, so a flag set to `false`
+/// disables the feature even if it is enabled by default.
+/// See [`OptionsBuilder::xnnpack_options()`].
+#[cfg(feature = "xnnpack")]
+#[cfg_attr(docsrs, doc(cfg(feature = "xnnpack")))]
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub struct XnnpackOptions {
+    /// Forces FP16 inference for FP32 operators, trading accuracy for speed.
+    ///
+    /// - Note: It is slow on processors without native FP16 arithmetic.
+    pub force_fp16: bool,
+
+    /// Enables operators with symmetric (signed) 8-bit quantization.
+    pub enable_qs8: bool,
+
+    /// Enables operators with asymmetric (unsigned) 8-bit quantization.
+    pub enable_qu8: bool,
+
+    /// Enables fully-connected operators with dynamic (non-constant) weights.
+    pub dynamic_fully_connected: bool,
+
+    /// Enables transient indirection buffers to reduce memory usage of convolutions.
+    pub transient_indirection_buffer: bool,
+
+    /// Enables delegating variable operators (`VAR_HANDLE`, `READ_VARIABLE` and
+    /// `ASSIGN_VARIABLE`) to XNNPACK.
+    pub handle_variable_ops: bool,
+}
+
+#[cfg(feature = "xnnpack")]
+impl XnnpackOptions {
+    /// Returns the options enabled by the given flags of `TfLiteXNNPackDelegateOptions`.
+    fn from_flags(flags: u32) -> XnnpackOptions {
+        let is_set = |flag: u32| flags & flag != 0;
+        XnnpackOptions {
+            force_fp16: is_set(TFLITE_XNNPACK_DELEGATE_FLAG_FORCE_FP16),
+            enable_qs8: is_set(TFLITE_XNNPACK_DELEGATE_FLAG_QS8),
+            enable_qu8: is_set(TFLITE_XNNPACK_DELEGATE_FLAG_QU8),
+            dynamic_fully_connected: is_set(TFLITE_XNNPACK_DELEGATE_FLAG_DYNAMIC_FULLY_CONNECTED),
+            transient_indirection_buffer: is_set(
+                TFLITE_XNNPACK_DELEGATE_FLAG_TRANSIENT_INDIRECTION_BUFFER,
+            ),
+            handle_variable_ops: is_set(TFLITE_XNNPACK_DELEGATE_FLAG_VARIABLE_OPERATORS),
+        }
+    }
+
+    /// Returns `flags` of `TfLiteXNNPackDelegateOptions` with the flag of each option set or
+    /// cleared, other flags are kept.
+    fn apply(&self, flags: u32) -> u32 {
+        [
+            (self.force_fp16, TFLITE_XNNPACK_DELEGATE_FLAG_FORCE_FP16),
+            (self.enable_qs8, TFLITE_XNNPACK_DELEGATE_FLAG_QS8),
+            (self.enable_qu8, TFLITE_XNNPACK_DELEGATE_FLAG_QU8),
+            (
+                self.dynamic_fully_connected,
+                TFLITE_XNNPACK_DELEGATE_FLAG_DYNAMIC_FULLY_CONNECTED,
+            ),
+            (
+                self.transient_indirection_buffer,
+                TFLITE_XNNPACK_DELEGATE_FLAG_TRANSIENT_INDIRECTION_BUFFER,
+            ),
+            (
+                self.handle_variable_ops,
+                TFLITE_XNNPACK_DELEGATE_FLAG_VARIABLE_OPERATORS,
+            ),
+        ]
+        .iter()
+        .fold(
+            flags,
+            |flags, &(enabled, flag)| {
+                if enabled {
+                    flags | flag
+                } else {
+                    flags & !flag
+                }
+            },
+        )
+    }
+}
+
+#[cfg(feature = "xnnpack")]
+impl Default for XnnpackOptions {
+    /// Returns the defaults of the XNNPACK delegate in the TensorFlow Lite build.
+    fn default() -> Self {
+        XnnpackOptions::from_flags(unsafe { TfLiteXNNPackDelegateOptionsDefault() }.flags)
+    }
+}
+
+/// A cache of packed weights shared by the XNNPACK delegates of several [`Interpreter`]s.
+///
+/// By default, each XNNPACK delegate repacks all weights of the model for itself. Interpreters
+/// created from the same [`Model`] with the same `WeightCache` share the packed weights instead,
+/// which reduces memory usage and startup time. The cache is either kept in process memory
+/// ([`WeightCache::new()`]) or backed by a file ([`WeightCache::from_file()`]), which is reused
+/// across processes as well.
+///
+/// Cloning a `WeightCache` returns a handle to the same cache.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tflitec::interpreter::{Interpreter, Options, WeightCache};
+/// use tflitec::model::Model;
+/// let model = Model::new("tests/add.bin")?;
+/// let options = Options::builder()
+///     .xnnpack_weight_cache(WeightCache::new()?)
+///     .build()?;
+/// let interpreters = (0..4)
+///     .map(|_| Interpreter::new(&model, Some(options.clone())))
+///     .collect::<tflitec::Result<Vec<_>>>()?;
+/// # Ok::<(), tflitec::Error>(())
+/// ```
+#[cfg(feature = "xnnpack")]
+#[cfg_attr(docsrs, doc(cfg(feature = "xnnpack")))]
+#[derive(Clone, Debug)]
+pub struct WeightCache {
+    inner: Arc<WeightCacheInner>,
+}
+
+#[cfg(feature = "xnnpack")]
+#[derive(Debug)]
+enum WeightCacheInner {
+    /// A cache in process memory, finalized after its first use.
+    InMemory {
+        cache_ptr: *mut TfLiteXNNPackDelegateWeightsCache,
+        is_finalized: Mutex<bool>,
+    },
+    /// A cache backed by the file at the given path.
+    File(std::ffi::CString),
+}
+
+#[cfg(feature = "xnnpack")]
+unsafe impl Send for WeightCacheInner {}
+#[cfg(feature = "xnnpack")]
+unsafe impl Sync for WeightCacheInner {}
+
+#[cfg(feature = "xnnpack")]
+impl WeightCache {
+    /// Creates a new cache in process memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if TensorFlow Lite C fails to create the cache.
+    pub fn new() -> Result<WeightCache> {
+        WeightCache::from_raw(unsafe { TfLiteXNNPackDelegateWeightsCacheCreate() })
+    }
+
+    /// Creates a new cache in process memory, reserving `size` bytes in advance.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if TensorFlow Lite C fails to create the cache.
+    pub fn with_size(size: usize) -> Result<WeightCache> {
+        WeightCache::from_raw(unsafe { TfLiteXNNPackDelegateWeightsCacheCreateWithSize(size) })
+    }
+
+    /// Creates a new cache backed by the file at the given `path`.
+    ///
+    /// The file is created by the first delegate using the cache and loaded by the
+    /// subsequent ones, including those in other processes.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if `path` contains a nul byte.
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<WeightCache> {
+        let path = path.as_ref().to_string_lossy().into_owned();
+        let path = std::ffi::CString::new(path)
+            .map_err(|_| Error::new(ErrorKind::FailedToCreateWeightCache))?;
+        Ok(WeightCache {
+            inner: Arc::new(WeightCacheInner::File(path)),
+        })
+    }
+
+    fn from_raw(cache_ptr: *mut TfLiteXNNPackDelegateWeightsCache) -> Result<WeightCache> {
+        if cache_ptr.is_null() {
+            return Err(Error::new(ErrorKind::FailedToCreateWeightCache));
+        }
+        Ok(WeightCache {
+            inner: Arc::new(WeightCacheInner::InMemory {
+                cache_ptr,
+                is_finalized: Mutex::new(false),
+            }),
+        })
+    }
+
+    /// Sets the cache in the given XNNPACK delegate options.
+    fn configure(&self, xnnpack_options: &mut TfLiteXNNPackDelegateOptions) {
+        match self.inner.as_ref() {
+            WeightCacheInner::InMemory { cache_ptr, .. } => {
+                xnnpack_options.weights_cache = *cache_ptr;
+            }
+            WeightCacheInner::File(path) => {
+                xnnpack_options.weight_cache_file_path = path.as_ptr();
+            }
+        }
+    }
+
+    /// Finalizes the in-memory cache once the first interpreter packed its weights.
+    ///
+    /// The cache is soft-finalized, so interpreters created later can still use it
+    /// as long as they pack the same weights.
+    fn finalize(&self) -> Result<()> {
+        if let WeightCacheInner::InMemory {
+            cache_ptr,
+            is_finalized,
+        } = self.inner.as_ref()
+        {
+            let mut is_finalized = is_finalized.lock().unwrap();
+            if !*is_finalized {
+                if !unsafe { TfLiteXNNPackDelegateWeightsCacheFinalizeSoft(*cache_ptr) } {
+                    return Err(Error::new(ErrorKind::FailedToFinalizeWeightCache));
+                }
+                *is_finalized = true;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "xnnpack")]
+impl Drop for WeightCacheInner {
+    fn drop(&mut self) {
+        if let WeightCacheInner::InMemory { cache_ptr, .. } = self {
+            unsafe { TfLiteXNNPackDelegateWeightsCacheDelete(*cache_ptr) }
+        }
+    }
+}
+
 /// Options for configuring the [`Interpreter`].
-#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, Ord, PartialOrd)]
+///
+/// Settings which are not exposed as public fields can be configured with
+/// [`OptionsBuilder`], see [`Options::builder()`].
+///
+/// - Note: `Options` has private fields and gains new fields over time, so it cannot be
+/// built with a struct literal outside of this crate. Use [`Options::builder()`], or set the
+/// public fields of [`Options::default()`] instead.
+#[derive(Clone)]
 pub struct Options {
     /// The maximum number of CPU threads that the interpreter should run on.
     ///
@@ -22,6 +279,15 @@ pub struct Options {
     /// implementation-defined and platform-dependent.
     pub thread_count: i32,
 
+    /// Indicates whether all intermediate tensors are kept alive after [`Interpreter::invoke()`].
+    ///
+    /// By default, TensorFlow Lite reuses the memory of intermediate tensors, so only inputs and
+    /// outputs hold meaningful data after inference. Enable this flag to read every tensor via
+    /// [`Interpreter::tensor()`] or [`Interpreter::tensors()`], e.g. for debugging or calibration.
+    ///
+    /// - Note: Enabling this flag increases memory consumption of the interpreter.
+    pub preserve_all_tensors: bool,
+
     /// Indicates whether an optimized set of floating point CPU kernels, provided by XNNPACK, is
     /// enabled.
     ///
@@ -36,22 +302,349 @@ pub struct Options {
     /// * Startup time and resize time may increase.
     /// * Baseline memory consumption may increase.
     /// * Quantized models will not see any benefit unless features `xnnpack_qu8` or `xnnpack_qs8`
-    /// are enabled.
+    /// are enabled, or quantized operators are enabled with [`XnnpackOptions`].
     #[cfg(feature = "xnnpack")]
     #[cfg_attr(docsrs, doc(cfg(feature = "xnnpack")))]
     pub is_xnnpack_enabled: bool,
+
+    /// The options of the XNNPACK delegate, used if `is_xnnpack_enabled` is `true`.
+    #[cfg(feature = "xnnpack")]
+    xnnpack_options: XnnpackOptions,
+
+    /// The cache of packed weights of the XNNPACK delegate.
+    #[cfg(feature = "xnnpack")]
+    xnnpack_weight_cache: Option<WeightCache>,
+
+    /// The pool bounding the number of threads used by all attached interpreters.
+    thread_pool: Option<ThreadPool>,
+
+    /// Indicates whether the [`Interpreter`] falls back to the CPU kernels if a delegate
+    /// fails to be applied.
+    enable_delegate_fallback: bool,
+
+    /// Indicates whether [`Interpreter::invoke()`] can be cancelled with a [`CancellationToken`].
+    enable_cancellation: bool,
+
+    /// The function receiving errors reported by TensorFlow Lite.
+    error_reporter: Option<ErrorReporter>,
+
+    /// The receiver of telemetry events.
+    telemetry_profiler: Option<Arc<dyn TelemetryProfiler>>,
+
+    /// The factories of user-supplied delegates, in the order they are applied.
+    delegates: Vec<Arc<dyn DelegateFactory>>,
+
+    /// The recorder of interpreter lifecycle events.
+    trace_recorder: Option<Arc<TraceRecorder>>,
+
+    /// The metrics configuration of the interpreter.
+    metrics: Option<Metrics>,
+
+    /// The validator of the operators of the model, applied before the interpreter is created.
+    model_validator: Option<Arc<ModelValidator>>,
+}
+
+impl Debug for Options {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut debug_struct = f.debug_struct("Options");
+        debug_struct
+            .field("thread_count", &self.thread_count)
+            .field("preserve_all_tensors", &self.preserve_all_tensors);
+        #[cfg(feature = "xnnpack")]
+        debug_struct
+            .field("is_xnnpack_enabled", &self.is_xnnpack_enabled)
+            .field("xnnpack_options", &self.xnnpack_options)
+            .field("xnnpack_weight_cache", &self.xnnpack_weight_cache);
+        debug_struct
+            .field("thread_pool", &self.thread_pool)
+            .field("enable_delegate_fallback", &self.enable_delegate_fallback)
+            .field("enable_cancellation", &self.enable_cancellation)
+            .field("error_reporter", &self.error_reporter.is_some())
+            .field("telemetry_profiler", &self.telemetry_profiler.is_some())
+            .field(
+                "delegates",
+                &self.delegates.iter().map(|d| d.name()).collect::<Vec<_>>(),
+            )
+            .field("trace_recorder", &self.trace_recorder.is_some())
+            .field("metrics", &self.metrics)
+            .field("model_validator", &self.model_validator)
+            .finish()
+    }
 }
 
 impl Default for Options {
     fn default() -> Self {
         Self {
             thread_count: -1,
+            preserve_all_tensors: false,
             #[cfg(feature = "xnnpack")]
             is_xnnpack_enabled: false,
+            #[cfg(feature = "xnnpack")]
+            xnnpack_options: XnnpackOptions::default(),
+            #[cfg(feature = "xnnpack")]
+            xnnpack_weight_cache: None,
+            thread_pool: None,
+            enable_delegate_fallback: false,
+            enable_cancellation: false,
+            error_reporter: None,
+            telemetry_profiler: None,
+            delegates: Vec::new(),
+            trace_recorder: None,
+            metrics: None,
+            model_validator: None,
+        }
+    }
+}
+
+impl Options {
+    /// Returns a new [`OptionsBuilder`] initialized with the default options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tflitec::interpreter::Options;
+    /// let options = Options::builder()
+    ///     .thread_count(2)
+    ///     .enable_cancellation(true)
+    ///     .error_reporter(|message| eprintln!("TensorFlow Lite error: {}", message))
+    ///     .build()?;
+    /// assert_eq!(options.thread_count, 2);
+    /// assert!(Options::builder().thread_count(-2).build().is_err());
+    /// # Ok::<(), tflitec::Error>(())
+    /// ```
+    pub fn builder() -> OptionsBuilder {
+        OptionsBuilder::default()
+    }
+
+    /// Returns the options of the XNNPACK delegate.
+    #[cfg(feature = "xnnpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "xnnpack")))]
+    pub fn xnnpack_options(&self) -> &XnnpackOptions {
+        &self.xnnpack_options
+    }
+
+    /// Returns the cache of packed weights of the XNNPACK delegate.
+    #[cfg(feature = "xnnpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "xnnpack")))]
+    pub fn xnnpack_weight_cache(&self) -> Option<&WeightCache> {
+        self.xnnpack_weight_cache.as_ref()
+    }
+
+    /// Returns the [`ThreadPool`] shared with other interpreters.
+    pub fn thread_pool(&self) -> Option<&ThreadPool> {
+        self.thread_pool.as_ref()
+    }
+
+    /// Returns the number of threads the [`Interpreter`] uses, bounded by the [`ThreadPool`].
+    fn effective_thread_count(&self) -> i32 {
+        match self.thread_pool.as_ref() {
+            Some(thread_pool) => thread_pool.effective_thread_count(self.thread_count) as i32,
+            None => self.thread_count,
+        }
+    }
+
+    /// Returns whether the [`Interpreter`] falls back to the CPU kernels if a delegate
+    /// fails to be applied.
+    pub fn is_delegate_fallback_enabled(&self) -> bool {
+        self.enable_delegate_fallback
+    }
+
+    /// Returns whether [`Interpreter::invoke()`] can be cancelled.
+    pub fn is_cancellation_enabled(&self) -> bool {
+        self.enable_cancellation
+    }
+
+    /// Returns the factories of user-supplied delegates.
+    pub fn delegates(&self) -> &[Arc<dyn DelegateFactory>] {
+        &self.delegates
+    }
+
+    /// Returns the recorder of interpreter lifecycle events.
+    pub fn trace_recorder(&self) -> Option<&Arc<TraceRecorder>> {
+        self.trace_recorder.as_ref()
+    }
+
+    /// Returns the metrics configuration of the interpreter.
+    pub fn metrics(&self) -> Option<&Metrics> {
+        self.metrics.as_ref()
+    }
+
+    /// Returns the validator of the operators of the model.
+    pub fn model_validator(&self) -> Option<&ModelValidator> {
+        self.model_validator.as_deref()
+    }
+
+    /// Checks that all values are in their valid ranges.
+    fn validate(&self) -> Result<()> {
+        if self.thread_count < -1 {
+            return Err(Error::new(ErrorKind::InvalidThreadCount(self.thread_count)));
+        }
+        Ok(())
+    }
+}
+
+/// A builder of [`Options`] covering all settings of the [`Interpreter`].
+///
+/// See [`Options::builder()`].
+#[derive(Clone, Debug, Default)]
+pub struct OptionsBuilder {
+    options: Options,
+}
+
+impl OptionsBuilder {
+    /// Sets the maximum number of CPU threads, see [`Options::thread_count`].
+    pub fn thread_count(mut self, thread_count: i32) -> Self {
+        self.options.thread_count = thread_count;
+        self
+    }
+
+    /// Sets whether all intermediate tensors are preserved,
+    /// see [`Options::preserve_all_tensors`].
+    pub fn preserve_all_tensors(mut self, preserve_all_tensors: bool) -> Self {
+        self.options.preserve_all_tensors = preserve_all_tensors;
+        self
+    }
+
+    /// Sets whether XNNPACK delegate is enabled, see [`Options::is_xnnpack_enabled`].
+    #[cfg(feature = "xnnpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "xnnpack")))]
+    pub fn xnnpack_enabled(mut self, is_xnnpack_enabled: bool) -> Self {
+        self.options.is_xnnpack_enabled = is_xnnpack_enabled;
+        self
+    }
+
+    /// Sets the options of the XNNPACK delegate and enables it.
+    #[cfg(feature = "xnnpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "xnnpack")))]
+    pub fn xnnpack_options(mut self, xnnpack_options: XnnpackOptions) -> Self {
+        self.options.is_xnnpack_enabled = true;
+        self.options.xnnpack_options = xnnpack_options;
+        self
+    }
+
+    /// Sets the cache of packed weights shared with other interpreters and enables the
+    /// XNNPACK delegate.
+    #[cfg(feature = "xnnpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "xnnpack")))]
+    pub fn xnnpack_weight_cache(mut self, weight_cache: WeightCache) -> Self {
+        self.options.is_xnnpack_enabled = true;
+        self.options.xnnpack_weight_cache = Some(weight_cache);
+        self
+    }
+
+    /// Sets the [`ThreadPool`] bounding the number of threads used by this and other
+    /// interpreters, for both the default CPU backend and the XNNPACK delegate.
+    ///
+    /// [`Options::thread_count`] is capped by the size of the pool, and -1 uses the
+    /// whole pool.
+    pub fn thread_pool(mut self, thread_pool: ThreadPool) -> Self {
+        self.options.thread_pool = Some(thread_pool);
+        self
+    }
+
+    /// Sets whether the [`Interpreter`] falls back to the CPU kernels if a delegate
+    /// fails to be applied, instead of failing to be created.
+    pub fn enable_delegate_fallback(mut self, enable: bool) -> Self {
+        self.options.enable_delegate_fallback = enable;
+        self
+    }
+
+    /// Sets whether [`Interpreter::invoke()`] can be cancelled with a [`CancellationToken`],
+    /// see [`Interpreter::cancellation_token()`].
+    pub fn enable_cancellation(mut self, enable: bool) -> Self {
+        self.options.enable_cancellation = enable;
+        self
+    }
+
+    /// Sets the function receiving errors reported by TensorFlow Lite.
+    ///
+    /// By default, TensorFlow Lite writes errors to the standard error, or with the `tracing`
+    /// or `log` feature they are routed as errors of those crates, see the `logging` module.
+    /// Errors of external delegate libraries are reported to it as well.
+    pub fn error_reporter<F>(mut self, reporter: F) -> Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        self.options.error_reporter = Some(Arc::new(reporter));
+        self
+    }
+
+    /// Sets the receiver of telemetry events of the [`Interpreter`].
+    pub fn telemetry_profiler(mut self, profiler: Arc<dyn TelemetryProfiler>) -> Self {
+        self.options.telemetry_profiler = Some(profiler);
+        self
+    }
+
+    /// Adds a delegate which will be applied in the order of addition.
+    pub fn add_delegate(mut self, delegate: Arc<dyn DelegateFactory>) -> Self {
+        self.options.delegates.push(delegate);
+        self
+    }
+
+    /// Sets the recorder of [`Interpreter::new()`], [`Interpreter::allocate_tensors()`] and
+    /// [`Interpreter::invoke()`] calls, see [`TraceRecorder`].
+    pub fn trace_recorder(mut self, recorder: Arc<TraceRecorder>) -> Self {
+        self.options.trace_recorder = Some(recorder);
+        self
+    }
+
+    /// Sets the metrics of [`Interpreter::invoke()`], [`Interpreter::allocate_tensors()`] and
+    /// [`Interpreter::resize_input()`] calls, see [`crate::metrics`].
+    pub fn metrics(mut self, metrics: Metrics) -> Self {
+        self.options.metrics = Some(metrics);
+        self
+    }
+
+    /// Sets the validator of the operators of the model, so that [`Interpreter::new()`] fails
+    /// with [`ErrorKind::DisallowedOperator`] if the model uses an operator it does not allow.
+    ///
+    /// A model is only validated once by each validator, so interpreters of pools, caches and
+    /// workers created with the same options do not validate it again.
+    pub fn model_validator(mut self, validator: ModelValidator) -> Self {
+        self.options.model_validator = Some(Arc::new(validator));
+        self
+    }
+
+    /// Returns the built [`Options`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error if a value is out of its valid range, e.g. `thread_count` is less than -1.
+    pub fn build(self) -> Result<Options> {
+        self.options.validate()?;
+        Ok(self.options)
+    }
+}
+
+/// A handle to cancel [`Interpreter::invoke()`] calls from another thread.
+///
+/// Cancelling has no effect after the [`Interpreter`] is dropped.
+#[derive(Clone, Debug)]
+pub struct CancellationToken {
+    interpreter_ptr: Arc<Mutex<Option<InterpreterPtr>>>,
+}
+
+#[derive(Debug)]
+struct InterpreterPtr(*mut TfLiteInterpreter);
+
+unsafe impl Send for InterpreterPtr {}
+
+impl CancellationToken {
+    /// Cancels the ongoing [`Interpreter::invoke()`] call, which then returns an error of kind
+    /// [`ErrorKind::InvokeCancelled`].
+    pub fn cancel(&self) {
+        let interpreter_ptr = self.interpreter_ptr.lock().unwrap();
+        if let Some(interpreter_ptr) = interpreter_ptr.as_ref() {
+            unsafe {
+                TfLiteInterpreterCancel(interpreter_ptr.0);
+            }
         }
     }
 }
 
+/// The identifier of the next [`Interpreter`].
+static NEXT_INTERPRETER_ID: AtomicUsize = AtomicUsize::new(0);
+
 /// A TensorFlow Lite interpreter that performs inference from a given model.
 ///
 /// - Note: Interpreter instances are *not* thread-safe.
@@ -59,6 +652,9 @@ pub struct Interpreter<'a> {
     /// The configuration options for the [`Interpreter`].
     options: Option<Options>,
 
+    /// The identifier of the interpreter in trace events, unique in the process.
+    id: usize,
+
     /// The underlying [`TfLiteInterpreter`] C pointer.
     interpreter_ptr: *mut TfLiteInterpreter,
 
@@ -66,6 +662,37 @@ pub struct Interpreter<'a> {
     #[cfg(feature = "xnnpack")]
     xnnpack_delegate_ptr: Option<*mut TfLiteDelegate>,
 
+    /// The user-supplied delegates, deleted after the interpreter.
+    #[allow(dead_code)]
+    delegates: Vec<RawDelegate>,
+
+    /// The delegates inspecting the applied delegates, deleted after the interpreter, see
+    /// [`Interpreter::delegation_report()`].
+    #[allow(dead_code)]
+    delegation_inspector: DelegationInspector,
+
+    /// The report of the delegates applied when the interpreter was created.
+    delegation_report: DelegationReport,
+
+    /// The error reporter whose address is passed to TensorFlow Lite C.
+    #[allow(dead_code)]
+    error_reporter: Option<Box<ErrorReporter>>,
+
+    /// The telemetry profiler whose address is passed to TensorFlow Lite C.
+    #[allow(dead_code)]
+    telemetry_profiler: Option<Box<TelemetryContext>>,
+
+    /// The C struct of `telemetry_profiler`, which must outlive the interpreter.
+    #[allow(dead_code)]
+    telemetry_profiler_struct: Option<Box<TfLiteTelemetryProfilerStruct>>,
+
+    /// The indices among all tensors of the tensors whose buffers were released by
+    /// [`CustomAllocations`], while TensorFlow Lite C still keeps their addresses.
+    released_allocations: HashSet<i32>,
+
+    /// The shared pointer for [`CancellationToken`]s, reset when the interpreter is dropped.
+    cancellation: Option<Arc<Mutex<Option<InterpreterPtr>>>>,
+
     /// The underlying `Model` to limit lifetime of the interpreter.
     /// See this issue for details:
     /// <https://github.com/tensorflow/tensorflow/issues/53628>
@@ -102,16 +729,129 @@ impl<'a> Interpreter<'a> {
     ///
     /// # Errors
     ///
-    /// Returns error if TensorFlow Lite C fails internally.
+    /// Returns error if `options` contains an invalid value, the model uses an operator which is
+    /// not allowed by the [`ModelValidator`] of `options`, a delegate cannot be created or
+    /// TensorFlow Lite C fails internally.
     pub fn new(model: &'a Model<'a>, options: Option<Options>) -> Result<Interpreter<'a>> {
+        let id = NEXT_INTERPRETER_ID.fetch_add(1, Ordering::Relaxed);
+        let trace_recorder = options
+            .as_ref()
+            .and_then(|options| options.trace_recorder.clone());
+        let _span = trace_recorder
+            .as_ref()
+            .map(|recorder| recorder.span("new", id));
+        #[cfg(feature = "tracing")]
+        let _tracing_span = tracing::info_span!(
+            "Interpreter::new",
+            interpreter = id,
+            model = ?model.model_ptr,
+            thread_count = options.as_ref().map(|options| options.effective_thread_count()),
+            delegates = %options
+                .as_ref()
+                .map(|options| {
+                    options
+                        .delegates
+                        .iter()
+                        .map(|factory| factory.name())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                })
+                .unwrap_or_default(),
+            xnnpack = tracing::field::Empty,
+        )
+        .entered();
+        #[cfg(all(feature = "tracing", feature = "xnnpack"))]
+        _tracing_span.record(
+            "xnnpack",
+            matches!(options.as_ref(), Some(options) if options.is_xnnpack_enabled),
+        );
+        if let Some(options) = options.as_ref() {
+            options.validate()?;
+            if let Some(validator) = options.model_validator.as_ref() {
+                model.validate(validator)?;
+            }
+        }
+        let mut delegate_names = Vec::new();
+        if let Some(options) = options.as_ref() {
+            #[cfg(feature = "xnnpack")]
+            if options.is_xnnpack_enabled {
+                delegate_names.push("XNNPACK".to_string());
+            }
+            delegate_names.extend(
+                options
+                    .delegates
+                    .iter()
+                    .map(|factory| factory.name().to_string()),
+            );
+        }
+        let delegation_inspector = DelegationInspector::new(delegate_names)?;
+        let error_reporter = options
+            .as_ref()
+            .and_then(|options| options.error_reporter.clone());
+        #[cfg(any(feature = "tracing", feature = "log"))]
+        let error_reporter = error_reporter.or_else(|| Some(crate::logging::error_reporter()));
+        let create_delegates = || {
+            options
+                .as_ref()
+                .map(|options| {
+                    options
+                        .delegates
+                        .iter()
+                        .map(|factory| factory.create_delegate())
+                        .collect::<Result<Vec<_>>>()
+                })
+                .transpose()
+                .map(Option::unwrap_or_default)
+        };
+        #[cfg(feature = "external_delegate")]
+        let delegates =
+            crate::delegate::with_plugin_error_reporter(error_reporter.as_ref(), create_delegates)?;
+        #[cfg(not(feature = "external_delegate"))]
+        let delegates = create_delegates()?;
+        let error_reporter = error_reporter.map(Box::new);
+        let telemetry_profiler = options
+            .as_ref()
+            .and_then(|options| options.telemetry_profiler.clone())
+            .map(|profiler| {
+                Box::new(TelemetryContext::new(
+                    profiler,
+                    delegation_inspector.kernel_delegates(),
+                ))
+            });
+        let telemetry_profiler_struct = telemetry_profiler
+            .as_ref()
+            .map(|profiler| Box::new(profiler::telemetry_profiler_struct(profiler)));
         unsafe {
             let options_ptr = TfLiteInterpreterOptionsCreate();
             if options_ptr.is_null() {
                 return Err(Error::new(ErrorKind::FailedToCreateInterpreter));
             }
-            if let Some(thread_count) = options.as_ref().map(|s| s.thread_count) {
+            if let Some(thread_count) = options.as_ref().map(|s| s.effective_thread_count()) {
                 TfLiteInterpreterOptionsSetNumThreads(options_ptr, thread_count);
             }
+            if let Some(true) = options.as_ref().map(|s| s.preserve_all_tensors) {
+                TfLiteInterpreterOptionsSetPreserveAllTensors(options_ptr, true);
+            }
+            if let Some(true) = options.as_ref().map(|s| s.enable_delegate_fallback) {
+                TfLiteInterpreterOptionsSetEnableDelegateFallback(options_ptr, true);
+            }
+            if let Some(true) = options.as_ref().map(|s| s.enable_cancellation) {
+                TfLiteInterpreterOptionsEnableCancellation(options_ptr, true);
+            }
+            if let Some(error_reporter) = error_reporter.as_ref() {
+                TfLiteInterpreterOptionsSetErrorReporter(
+                    options_ptr,
+                    Some(report_error),
+                    error_reporter.as_ref() as *const ErrorReporter as *mut c_void,
+                );
+            }
+            if let Some(profiler_struct) = telemetry_profiler_struct.as_ref() {
+                TfLiteInterpreterOptionsSetTelemetryProfiler(
+                    options_ptr,
+                    profiler_struct.as_ref() as *const TfLiteTelemetryProfilerStruct
+                        as *mut TfLiteTelemetryProfilerStruct,
+                );
+            }
 
             #[cfg(feature = "xnnpack")]
             let mut xnnpack_delegate_ptr: Option<*mut TfLiteDelegate> = None;
@@ -119,30 +859,99 @@ impl<'a> Interpreter<'a> {
             {
                 if let Some(options) = options.as_ref() {
                     if options.is_xnnpack_enabled {
-                        xnnpack_delegate_ptr =
-                            Some(Interpreter::configure_xnnpack(options, options_ptr));
+                        let delegate_ptr = Interpreter::configure_xnnpack(options, options_ptr);
+                        TfLiteInterpreterOptionsAddDelegate(
+                            options_ptr,
+                            delegation_inspector.inspect(0),
+                        );
+                        xnnpack_delegate_ptr = Some(delegate_ptr);
                     }
                 }
             }
+            let position_offset = delegation_inspector.len() - delegates.len();
+            for (position, delegate) in delegates.iter().enumerate() {
+                TfLiteInterpreterOptionsAddDelegate(
+                    options_ptr,
+                    delegate.as_ptr() as *mut TfLiteOpaqueDelegate,
+                );
+                TfLiteInterpreterOptionsAddDelegate(
+                    options_ptr,
+                    delegation_inspector.inspect(position_offset + position),
+                );
+            }
 
-            // TODO(ebraraktas): TfLiteInterpreterOptionsSetErrorReporter
             let model_ptr = model.model_ptr as *const TfLiteModel;
             let interpreter_ptr = TfLiteInterpreterCreate(model_ptr, options_ptr);
             TfLiteInterpreterOptionsDelete(options_ptr);
             if interpreter_ptr.is_null() {
                 Err(Error::new(ErrorKind::FailedToCreateInterpreter))
             } else {
+                let delegation_report = delegation_inspector.report();
+                let cancellation = match options.as_ref() {
+                    Some(options) if options.enable_cancellation => {
+                        Some(Arc::new(Mutex::new(Some(InterpreterPtr(interpreter_ptr)))))
+                    }
+                    _ => None,
+                };
                 Ok(Interpreter {
                     options,
+                    id,
                     interpreter_ptr,
                     #[cfg(feature = "xnnpack")]
                     xnnpack_delegate_ptr,
+                    delegates,
+                    delegation_inspector,
+                    delegation_report,
+                    error_reporter,
+                    telemetry_profiler,
+                    telemetry_profiler_struct,
+                    released_allocations: HashSet::new(),
+                    cancellation,
                     model,
                 })
             }
         }
     }
 
+    /// Returns a [`CancellationToken`] to cancel [`Interpreter::invoke()`] calls from
+    /// another thread.
+    ///
+    /// returns: [`None`] unless cancellation is enabled with
+    /// [`OptionsBuilder::enable_cancellation()`].
+    pub fn cancellation_token(&self) -> Option<CancellationToken> {
+        self.cancellation
+            .as_ref()
+            .map(|interpreter_ptr| CancellationToken {
+                interpreter_ptr: interpreter_ptr.clone(),
+            })
+    }
+
+    /// Returns the report of the delegates applied when the interpreter was created.
+    ///
+    /// The report lists the XNNPACK delegate, if enabled, followed by the delegates added with
+    /// [`OptionsBuilder::add_delegate()`], along with the number of nodes each of them claimed.
+    ///
+    /// - Note: If a delegate fails and fallback is enabled with
+    /// [`OptionsBuilder::enable_delegate_fallback()`], the interpreter is created without any
+    /// delegate and [`DelegationReport::is_fallback()`] returns `true`. Otherwise,
+    /// [`Interpreter::new()`] returns error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tflitec::interpreter::{Interpreter, Options};
+    /// use tflitec::model::Model;
+    /// let options = Options::builder().enable_delegate_fallback(true).build()?;
+    /// let model = Model::new("tests/add.bin")?;
+    /// let interpreter = Interpreter::new(&model, Some(options))?;
+    /// let report = interpreter.delegation_report();
+    /// assert!(!report.is_fallback());
+    /// # Ok::<(), tflitec::Error>(())
+    /// ```
+    pub fn delegation_report(&self) -> &DelegationReport {
+        &self.delegation_report
+    }
+
     /// Returns the total number of input [`Tensor`]s associated with the model.
     pub fn input_tensor_count(&self) -> usize {
         unsafe { TfLiteInterpreterGetInputTensorCount(self.interpreter_ptr) as usize }
@@ -153,14 +962,62 @@ impl<'a> Interpreter<'a> {
         unsafe { TfLiteInterpreterGetOutputTensorCount(self.interpreter_ptr) as usize }
     }
 
+    /// Returns the total number of [`Tensor`]s associated with the model, including
+    /// intermediate ones.
+    ///
+    /// - Note: Only the tensors of the primary subgraph are counted. TensorFlow Lite C does not
+    /// expose the tensors of other subgraphs, e.g. the bodies of control flow operators.
+    pub fn tensor_count(&self) -> usize {
+        unsafe { TfLiteInterpreterGetTensorCount(self.interpreter_ptr) as usize }
+    }
+
+    /// Returns the total number of variable [`Tensor`]s associated with the model.
+    ///
+    /// Variable tensors hold the state of stateful models (e.g. RNN or LSTM) between
+    /// [`Interpreter::invoke()`] calls.
+    ///
+    /// - Note: Resource variables created by `VAR_HANDLE` operators are not tensors and are
+    /// neither counted nor exposed here, since TensorFlow Lite C has no API to access them.
+    pub fn variable_tensor_count(&self) -> usize {
+        unsafe { TfLiteInterpreterGetVariableTensorCount(self.interpreter_ptr) as usize }
+    }
+
     /// Invokes the interpreter to perform inference from the loaded graph.
     ///
+    /// If a [`ThreadPool`] is attached, this blocks until enough threads of the pool are free.
+    ///
     /// # Errors
     ///
-    /// Returns error if TensorFlow Lite C fails to invoke.
+    /// Returns error if TensorFlow Lite C fails to invoke, the invocation is cancelled
+    /// with a [`CancellationToken`], or a buffer of [`CustomAllocations`] was released without
+    /// binding a new one.
     pub fn invoke(&self) -> Result<()> {
-        if TfLiteStatus_kTfLiteOk == unsafe { TfLiteInterpreterInvoke(self.interpreter_ptr) } {
+        let _span = self.trace_span("invoke");
+        #[cfg(feature = "tracing")]
+        let _tracing_span =
+            tracing::debug_span!("Interpreter::invoke", interpreter = self.id).entered();
+        let start = Instant::now();
+        let result = self.invoke_unmetered();
+        if let Some(metrics) = self.metrics() {
+            metrics.record_invoke(start.elapsed(), &result);
+        }
+        result
+    }
+
+    fn invoke_unmetered(&self) -> Result<()> {
+        self.check_allocations()?;
+        let thread_pool = self
+            .options
+            .as_ref()
+            .and_then(|options| options.thread_pool.as_ref().map(|pool| (options, pool)));
+        let _threads = thread_pool.map(|(options, thread_pool)| {
+            thread_pool.acquire(options.effective_thread_count() as usize)
+        });
+        let status = unsafe { TfLiteInterpreterInvoke(self.interpreter_ptr) };
+        if status == TfLiteStatus_kTfLiteOk {
             Ok(())
+        } else if status == TfLiteStatus_kTfLiteCancelled {
+            Err(Error::new(ErrorKind::InvokeCancelled))
         } else {
             Err(Error::new(ErrorKind::AllocateTensorsRequired))
         }
@@ -183,6 +1040,10 @@ impl<'a> Interpreter<'a> {
             return Err(Error::new(ErrorKind::InvalidTensorIndex(index, max_index)));
         }
         unsafe {
+            self.check_allocation(TfLiteInterpreterGetInputTensorIndex(
+                self.interpreter_ptr,
+                index as i32,
+            ))?;
             let tensor_ptr = TfLiteInterpreterGetInputTensor(self.interpreter_ptr, index as i32);
             Tensor::from_raw(tensor_ptr as *mut TfLiteTensor).map_err(|error| {
                 if error.kind() == ErrorKind::ReadTensorError {
@@ -212,6 +1073,10 @@ impl<'a> Interpreter<'a> {
             return Err(Error::new(ErrorKind::InvalidTensorIndex(index, max_index)));
         }
         unsafe {
+            self.check_allocation(TfLiteInterpreterGetOutputTensorIndex(
+                self.interpreter_ptr,
+                index as i32,
+            ))?;
             let tensor_ptr = TfLiteInterpreterGetOutputTensor(self.interpreter_ptr, index as i32);
             Tensor::from_raw(tensor_ptr as *mut TfLiteTensor).map_err(|error| {
                 if error.kind() == ErrorKind::ReadTensorError {
@@ -223,23 +1088,331 @@ impl<'a> Interpreter<'a> {
         }
     }
 
-    /// Resizes the input [`Tensor`] at the given index to the
-    /// specified [`Shape`][tensor::Shape].
-    ///
-    /// - Note: After resizing an input tensor, the client **must** explicitly call
-    /// [`Interpreter::allocate_tensors()`] before attempting to access the resized tensor data
-    /// or invoking the interpreter to perform inference.
+    /// Returns the [`TensorInfo`][tensor::TensorInfo] of all input tensors, in order.
     ///
-    /// # Arguments
+    /// Unlike [`Interpreter::input()`], this does not require
+    /// [`Interpreter::allocate_tensors()`] to be called, so input sizes and preprocessing can be
+    /// chosen before paying for allocation.
     ///
-    /// * `index`: The index for the input [`Tensor`].
-    /// * `shape`: The shape to resize the input [`Tensor`] to.
+    /// # Examples
     ///
-    /// # Errors
+    /// ```
+    /// use tflitec::interpreter::Interpreter;
+    /// use tflitec::model::Model;
+    /// use tflitec::tensor::Shape;
+    ///
+    /// let model = Model::new("tests/add.bin")?;
+    /// let interpreter = Interpreter::new(&model, None)?;
+    /// let details = interpreter.input_details()?;
+    /// let mut dimensions = details[0].shape().dimensions().clone();
+    /// dimensions[0] = 4;
+    /// interpreter.resize_input(0, Shape::new(dimensions))?;
+    /// interpreter.allocate_tensors()?;
+    /// # Ok::<(), tflitec::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the data type of an input is unsupported or TensorFlow Lite C fails
+    /// internally.
+    pub fn input_details(&self) -> Result<Vec<tensor::TensorInfo>> {
+        (0..self.input_tensor_count())
+            .map(|index| unsafe {
+                tensor::TensorInfo::from_raw(TfLiteInterpreterGetInputTensor(
+                    self.interpreter_ptr,
+                    index as i32,
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns the [`TensorInfo`][tensor::TensorInfo] of all output tensors, in order.
+    ///
+    /// This does not require [`Interpreter::allocate_tensors()`] to be called, but output
+    /// shapes depending on resized inputs are only known after it.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the data type of an output is unsupported or TensorFlow Lite C fails
+    /// internally.
+    pub fn output_details(&self) -> Result<Vec<tensor::TensorInfo>> {
+        (0..self.output_tensor_count())
+            .map(|index| unsafe {
+                tensor::TensorInfo::from_raw(TfLiteInterpreterGetOutputTensor(
+                    self.interpreter_ptr,
+                    index as i32,
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns the number of signatures of the model, see [`crate::signature`].
+    pub fn signature_count(&self) -> usize {
+        unsafe { TfLiteInterpreterGetSignatureCount(self.interpreter_ptr) as usize }
+    }
+
+    /// Returns the keys of the signatures of the model, in order.
+    pub fn signature_keys(&self) -> Vec<String> {
+        (0..self.signature_count())
+            .map(|index| unsafe {
+                let key_ptr = TfLiteInterpreterGetSignatureKey(self.interpreter_ptr, index as i32);
+                if key_ptr.is_null() {
+                    String::new()
+                } else {
+                    CStr::from_ptr(key_ptr).to_string_lossy().into_owned()
+                }
+            })
+            .collect()
+    }
+
+    /// Returns a [`SignatureRunner`] of the subgraph of the signature with the given `key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error of kind [`ErrorKind::UnknownSignatureKey`] listing the keys of all
+    /// signatures if the model has no signature with the given key, or error if TensorFlow
+    /// Lite C fails to create the runner, or a buffer of [`CustomAllocations`] was released
+    /// without binding a new one.
+    pub fn signature_runner(&self, key: &str) -> Result<SignatureRunner<'_>> {
+        self.check_allocations()?;
+        let keys = self.signature_keys();
+        if !keys.iter().any(|signature_key| signature_key == key) {
+            return Err(Error::unknown_signature_key(key, keys));
+        }
+        SignatureRunner::new(self.interpreter_ptr, key)
+    }
+
+    /// Returns the index of the input [`Tensor`] with the given `name`.
+    ///
+    /// Names are stable across re-exports of a model which reorder its inputs, unlike indices.
+    /// This does not require [`Interpreter::allocate_tensors()`] to be called.
+    ///
+    /// # Errors
+    ///
+    /// Returns error of kind [`ErrorKind::UnknownTensorName`] listing the names of all inputs
+    /// if no input has the given name.
+    pub fn input_index(&self, name: &str) -> Result<usize> {
+        let names = self.input_names();
+        names
+            .iter()
+            .position(|input_name| input_name == name)
+            .ok_or_else(|| Error::unknown_tensor_name(name, names.clone()))
+    }
+
+    /// Returns the index of the output [`Tensor`] with the given `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error of kind [`ErrorKind::UnknownTensorName`] listing the names of all outputs
+    /// if no output has the given name.
+    pub fn output_index(&self, name: &str) -> Result<usize> {
+        let names = self.output_names();
+        names
+            .iter()
+            .position(|output_name| output_name == name)
+            .ok_or_else(|| Error::unknown_tensor_name(name, names.clone()))
+    }
+
+    /// Returns the input [`Tensor`] with the given `name`, see [`Interpreter::input()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error if no input has the given name or [`Interpreter::allocate_tensors()`]
+    /// was not called before calling this.
+    pub fn input_by_name(&self, name: &str) -> Result<Tensor> {
+        self.input(self.input_index(name)?)
+    }
+
+    /// Returns the output [`Tensor`] with the given `name`, see [`Interpreter::output()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error if no output has the given name or the output tensor has not been both
+    /// sized and allocated.
+    pub fn output_by_name(&self, name: &str) -> Result<Tensor> {
+        self.output(self.output_index(name)?)
+    }
+
+    /// Copies the given `data` to the input [`Tensor`] with the given `name`, see
+    /// [`Interpreter::copy()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error if no input has the given name, byte count of the data does not match
+    /// the buffer size of the input tensor or TensorFlow Lite C fails internally.
+    pub fn copy_by_name<T>(&self, data: &[T], name: &str) -> Result<()> {
+        self.copy(data, self.input_index(name)?)
+    }
+
+    fn input_names(&self) -> Vec<String> {
+        (0..self.input_tensor_count())
+            .map(|index| unsafe {
+                tensor_name(TfLiteInterpreterGetInputTensor(
+                    self.interpreter_ptr,
+                    index as i32,
+                ))
+            })
+            .collect()
+    }
+
+    fn output_names(&self) -> Vec<String> {
+        (0..self.output_tensor_count())
+            .map(|index| unsafe {
+                tensor_name(TfLiteInterpreterGetOutputTensor(
+                    self.interpreter_ptr,
+                    index as i32,
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns the variable [`Tensor`] at the given `index`.
+    ///
+    /// The state of a stateful model can be read from and written to the returned [`Tensor`],
+    /// e.g. to save and restore the state of independent streams processed by one interpreter.
+    ///
+    /// # Arguments
+    ///
+    /// * `index`: The index for the variable [`Tensor`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error if [`Interpreter::allocate_tensors()`] was not called before calling this
+    /// or given index is not a valid variable tensor index in
+    /// [0, [`Interpreter::variable_tensor_count()`]).
+    pub fn variable(&self, index: usize) -> Result<Tensor> {
+        let count = self.variable_tensor_count();
+        if index >= count {
+            return Err(Error::new(ErrorKind::InvalidTensorIndex(
+                index,
+                count.saturating_sub(1),
+            )));
+        }
+        unsafe {
+            let tensor_ptr = TfLiteInterpreterGetVariableTensor(self.interpreter_ptr, index as i32);
+            Tensor::from_raw(tensor_ptr).map_err(|error| {
+                if error.kind() == ErrorKind::ReadTensorError {
+                    Error::new(ErrorKind::AllocateTensorsRequired)
+                } else {
+                    error
+                }
+            })
+        }
+    }
+
+    /// Resets all variable [`Tensor`]s to their default values.
+    ///
+    /// Call this before processing a new independent sequence with a stateful model,
+    /// e.g. when switching the interpreter to another audio stream.
+    ///
+    /// - Note: Only variable tensors are reset. Resource variables created by `VAR_HANDLE`
+    /// operators keep their values, since TensorFlow Lite C has no API to access them.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if TensorFlow Lite C fails to reset the variable tensors.
+    pub fn reset_variable_tensors(&self) -> Result<()> {
+        if TfLiteStatus_kTfLiteOk
+            == unsafe { TfLiteInterpreterResetVariableTensors(self.interpreter_ptr) }
+        {
+            Ok(())
+        } else {
+            Err(Error::new(ErrorKind::FailedToResetVariableTensors))
+        }
+    }
+
+    /// Returns the [`Tensor`] at the given `index` among all tensors of the model.
+    ///
+    /// Unlike [`Interpreter::input()`] and [`Interpreter::output()`], `index` refers to the list
+    /// of all tensors, so intermediate tensors can be accessed as well. Their data is meaningful
+    /// after [`Interpreter::invoke()`] only if [`Options::preserve_all_tensors`] is enabled.
+    /// Like [`Interpreter::tensor_count()`], only the primary subgraph is covered.
+    ///
+    /// # Arguments
+    ///
+    /// * `index`: The index for the [`Tensor`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error if given index is not a valid tensor index in
+    /// [0, [`Interpreter::tensor_count()`]) or the tensor has no allocated data.
+    pub fn tensor(&self, index: usize) -> Result<Tensor> {
+        let count = self.tensor_count();
+        if index >= count {
+            return Err(Error::new(ErrorKind::InvalidTensorIndex(
+                index,
+                count.saturating_sub(1),
+            )));
+        }
+        self.check_allocation(index as i32)?;
+        unsafe {
+            let tensor_ptr = TfLiteInterpreterGetTensor(self.interpreter_ptr, index as i32);
+            Tensor::from_raw(tensor_ptr)
+        }
+    }
+
+    /// Returns an iterator over all [`Tensor`]s of the model, in index order.
+    ///
+    /// See [`Interpreter::tensor()`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tflitec::model::Model;
+    /// use tflitec::interpreter::{Interpreter, Options};
+    /// let model = Model::new("tests/add.bin")?;
+    /// let options = Options::builder().preserve_all_tensors(true).build()?;
+    /// let interpreter = Interpreter::new(&model, Some(options))?;
+    /// interpreter.allocate_tensors()?;
+    /// interpreter.invoke()?;
+    /// for tensor in interpreter.tensors() {
+    ///     let tensor = tensor?;
+    ///     println!("{}: {:?}", tensor.name(), tensor.shape());
+    /// }
+    /// # Ok::<(), tflitec::Error>(())
+    /// ```
+    pub fn tensors(&self) -> Tensors<'_, 'a> {
+        Tensors {
+            interpreter: self,
+            index: 0,
+            count: self.tensor_count(),
+        }
+    }
+
+    /// Resizes the input [`Tensor`] at the given index to the
+    /// specified [`Shape`][tensor::Shape].
+    ///
+    /// - Note: After resizing an input tensor, the client **must** explicitly call
+    /// [`Interpreter::allocate_tensors()`] before attempting to access the resized tensor data
+    /// or invoking the interpreter to perform inference.
+    ///
+    /// # Arguments
+    ///
+    /// * `index`: The index for the input [`Tensor`].
+    /// * `shape`: The shape to resize the input [`Tensor`] to.
+    ///
+    /// # Errors
     ///
     /// Returns error if given index is not a valid input tensor index in
     /// [0, [`Interpreter::input_tensor_count()`]) or TensorFlow Lite C fails internally.
     pub fn resize_input(&self, index: usize, shape: tensor::Shape) -> Result<()> {
+        #[cfg(feature = "tracing")]
+        let _tracing_span = tracing::debug_span!(
+            "Interpreter::resize_input",
+            interpreter = self.id,
+            index,
+            shape = ?shape.dimensions()
+        )
+        .entered();
+        let _span = self.trace_span("resize_input");
+        let result = self.resize_input_unmetered(index, &shape);
+        if let Some(metrics) = self.metrics() {
+            metrics.record_resize(&result);
+        }
+        result
+    }
+
+    fn resize_input_unmetered(&self, index: usize, shape: &tensor::Shape) -> Result<()> {
         let max_index = self.input_tensor_count() - 1;
         if index > max_index {
             return Err(Error::new(ErrorKind::InvalidTensorIndex(index, max_index)));
@@ -275,15 +1448,45 @@ impl<'a> Interpreter<'a> {
     /// # Error
     ///
     /// Returns error if TensorFlow Lite C fails to allocate memory
-    /// for the input tensors.
+    /// for the input tensors, or to finalize the XNNPACK weight cache after packing weights
+    /// into it.
     pub fn allocate_tensors(&self) -> Result<()> {
+        let _span = self.trace_span("allocate_tensors");
+        #[cfg(feature = "tracing")]
+        let _tracing_span = tracing::debug_span!(
+            "Interpreter::allocate_tensors",
+            interpreter = self.id,
+            input_shapes = ?(0..self.input_tensor_count())
+                .filter_map(|index| self.input(index).ok())
+                .map(|tensor| tensor.shape().dimensions().clone())
+                .collect::<Vec<_>>()
+        )
+        .entered();
+        let start = Instant::now();
+        let result = self.allocate_tensors_unmetered();
+        if let Some(metrics) = self.metrics() {
+            metrics.record_allocate(start.elapsed(), &result);
+        }
+        result
+    }
+
+    fn allocate_tensors_unmetered(&self) -> Result<()> {
         if TfLiteStatus_kTfLiteOk
-            == unsafe { TfLiteInterpreterAllocateTensors(self.interpreter_ptr) }
+            != unsafe { TfLiteInterpreterAllocateTensors(self.interpreter_ptr) }
         {
-            Ok(())
-        } else {
-            Err(Error::new(ErrorKind::FailedToAllocateTensors))
+            return Err(Error::new(ErrorKind::FailedToAllocateTensors));
         }
+        #[cfg(feature = "xnnpack")]
+        {
+            if let Some(weight_cache) = self
+                .options
+                .as_ref()
+                .and_then(|options| options.xnnpack_weight_cache.as_ref())
+            {
+                weight_cache.finalize()?;
+            }
+        }
+        Ok(())
     }
 
     /// Copies the given `data` to the input [`Tensor`] at the given `index`.
@@ -304,6 +1507,10 @@ impl<'a> Interpreter<'a> {
             return Err(Error::new(ErrorKind::InvalidTensorIndex(index, max_index)));
         }
         unsafe {
+            self.check_allocation(TfLiteInterpreterGetInputTensorIndex(
+                self.interpreter_ptr,
+                index as i32,
+            ))?;
             let tensor_ptr = TfLiteInterpreterGetInputTensor(self.interpreter_ptr, index as i32);
             let byte_count = TfLiteTensorByteSize(tensor_ptr);
             if data.len() != byte_count {
@@ -342,19 +1549,104 @@ impl<'a> Interpreter<'a> {
         self.copy_bytes(d, index)
     }
 
+    /// Returns a guard which binds caller-owned buffers as memory of input and output
+    /// [`Tensor`]s, see [`CustomAllocations`].
+    pub fn custom_allocations<'b>(&mut self) -> CustomAllocations<'_, 'a, 'b> {
+        CustomAllocations {
+            interpreter: self,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            needs_allocation: false,
+            buffers: PhantomData,
+        }
+    }
+
+    /// Sets custom allocation of the tensor at `tensor_index` among all tensors.
+    ///
+    /// Callers must guarantee that `bytes` bytes at `data` stay valid until the allocation of
+    /// the tensor is replaced, or the tensor is added to `released_allocations`.
+    unsafe fn set_custom_allocation(
+        &self,
+        tensor_index: i32,
+        data: *mut u8,
+        bytes: usize,
+    ) -> Result<()> {
+        let tensor_ptr = TfLiteInterpreterGetTensor(self.interpreter_ptr, tensor_index);
+        check_custom_allocation(tensor_ptr, data, bytes)?;
+        let allocation = TfLiteCustomAllocation {
+            data: data as *mut c_void,
+            bytes,
+        };
+        let status = TfLiteInterpreterSetCustomAllocationForTensor(
+            self.interpreter_ptr,
+            tensor_index,
+            &allocation,
+            TfLiteCustomAllocationFlags_kTfLiteCustomAllocationFlagsNone as i64,
+        );
+        if status != TfLiteStatus_kTfLiteOk {
+            Err(Error::new(ErrorKind::FailedToSetCustomAllocation(
+                tensor_index as usize,
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns error if the buffer bound to any tensor was released by [`CustomAllocations`]
+    /// without binding a new one.
+    fn check_allocations(&self) -> Result<()> {
+        match self.released_allocations.iter().min() {
+            Some(&tensor_index) => Err(Error::new(ErrorKind::CustomAllocationReleased(
+                tensor_index as usize,
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns error if the buffer bound to the tensor at `tensor_index` among all tensors was
+    /// released by [`CustomAllocations`] without binding a new one.
+    fn check_allocation(&self, tensor_index: i32) -> Result<()> {
+        if self.released_allocations.contains(&tensor_index) {
+            return Err(Error::new(ErrorKind::CustomAllocationReleased(
+                tensor_index as usize,
+            )));
+        }
+        Ok(())
+    }
+
     /// Returns optional reference of [`Options`].
     pub fn options(&self) -> Option<&Options> {
         self.options.as_ref()
     }
 
+    /// Returns the [`Metrics`] of the options, if any.
+    fn metrics(&self) -> Option<&Metrics> {
+        self.options
+            .as_ref()
+            .and_then(|options| options.metrics.as_ref())
+    }
+
+    /// Starts a trace event of the [`TraceRecorder`] of the options, if any.
+    fn trace_span(&self, name: &'static str) -> Option<TraceSpan<'_>> {
+        self.options
+            .as_ref()
+            .and_then(|options| options.trace_recorder.as_ref())
+            .map(|recorder| recorder.span(name, self.id))
+    }
+
     #[cfg(feature = "xnnpack")]
     unsafe fn configure_xnnpack(
         options: &Options,
         interpreter_options_ptr: *mut TfLiteInterpreterOptions,
     ) -> *mut TfLiteDelegate {
         let mut xnnpack_options = TfLiteXNNPackDelegateOptionsDefault();
-        if options.thread_count > 0 {
-            xnnpack_options.num_threads = options.thread_count
+        let thread_count = options.effective_thread_count();
+        if thread_count > 0 {
+            xnnpack_options.num_threads = thread_count
+        }
+        xnnpack_options.flags = options.xnnpack_options.apply(xnnpack_options.flags);
+        if let Some(weight_cache) = options.xnnpack_weight_cache.as_ref() {
+            weight_cache.configure(&mut xnnpack_options);
         }
 
         let xnnpack_delegate_ptr = TfLiteXNNPackDelegateCreate(&xnnpack_options);
@@ -363,9 +1655,345 @@ impl<'a> Interpreter<'a> {
     }
 }
 
+/// An iterator over all [`Tensor`]s of an [`Interpreter`].
+///
+/// This struct is created by [`Interpreter::tensors()`].
+#[derive(Debug)]
+pub struct Tensors<'i, 'a> {
+    interpreter: &'i Interpreter<'a>,
+    index: usize,
+    count: usize,
+}
+
+impl<'i> Iterator for Tensors<'i, '_> {
+    type Item = Result<Tensor<'i>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.count {
+            return None;
+        }
+        let tensor = self.interpreter.tensor(self.index);
+        self.index += 1;
+        Some(tensor)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.count - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Tensors<'_, '_> {}
+
+/// A guard which binds caller-owned buffers as memory of input and output [`Tensor`]s of an
+/// [`Interpreter`], so that large inputs and outputs are not copied from and to the arena of
+/// the interpreter.
+///
+/// A buffer is bound as soon as it is assigned, and the tensors are allocated again before the
+/// next [`CustomAllocations::invoke()`], as TensorFlow Lite C requires after binding. The
+/// buffers stay borrowed for the lifetime `'b` of the guard, and can be read and written
+/// through it between invocations.
+///
+/// - Note: TensorFlow Lite C cannot remove custom allocations, so the interpreter still keeps
+/// the addresses of the buffers after the guard is dropped. Until new buffers are bound to the
+/// same tensors by another guard, the interpreter refuses to invoke and to access those tensors
+/// with an [`ErrorKind::CustomAllocationReleased`] error, and so do signature runners.
+///
+/// This struct is created by [`Interpreter::custom_allocations()`].
+///
+/// # Examples
+///
+/// ```
+/// use tflitec::interpreter::Interpreter;
+/// use tflitec::model::Model;
+///
+/// #[repr(C, align(64))]
+/// struct AlignedBuffer([f32; 8 * 8 * 3]);
+///
+/// let model = Model::new("tests/add.bin")?;
+/// let mut interpreter = Interpreter::new(&model, None)?;
+/// interpreter.allocate_tensors()?;
+/// let mut input = AlignedBuffer([1.0; 8 * 8 * 3]);
+/// let mut output = AlignedBuffer([0.0; 8 * 8 * 3]);
+/// let mut allocations = interpreter.custom_allocations();
+/// allocations.set_input_allocation(0, &mut input.0)?;
+/// allocations.set_output_allocation(0, &mut output.0)?;
+/// allocations.invoke()?;
+/// assert_eq!(allocations.output::<f32>(0).unwrap()[0], 3.0);
+/// allocations.input_mut::<f32>(0).unwrap()[0] = 2.0;
+/// allocations.invoke()?;
+/// assert_eq!(allocations.output::<f32>(0).unwrap()[0], 6.0);
+/// # Ok::<(), tflitec::Error>(())
+/// ```
+pub struct CustomAllocations<'i, 'a, 'b> {
+    interpreter: &'i mut Interpreter<'a>,
+
+    /// The buffers bound to input tensors, keyed by their input index.
+    inputs: HashMap<usize, BoundBuffer>,
+
+    /// The buffers bound to output tensors, keyed by their output index.
+    outputs: HashMap<usize, BoundBuffer>,
+
+    /// Whether a buffer was bound since the tensors were last allocated.
+    needs_allocation: bool,
+
+    // To borrow the bound buffers
+    buffers: PhantomData<&'b mut [u8]>,
+}
+
+/// A caller-owned buffer bound to a tensor.
+#[derive(Debug)]
+struct BoundBuffer {
+    tensor_index: i32,
+    data: *mut u8,
+    bytes: usize,
+}
+
+impl Debug for CustomAllocations<'_, '_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomAllocations")
+            .field("interpreter", &self.interpreter)
+            .field("inputs", &self.inputs.keys())
+            .field("outputs", &self.outputs.keys())
+            .finish()
+    }
+}
+
+impl<'b> CustomAllocations<'_, '_, 'b> {
+    /// Binds the caller-owned `buffer` as memory of the input [`Tensor`] at the given `index`,
+    /// replacing any buffer bound to it before.
+    ///
+    /// The interpreter reads the input directly from `buffer` instead of its internal arena.
+    ///
+    /// # Arguments
+    ///
+    /// * `index`: The index for the input [`Tensor`].
+    /// * `buffer`: The buffer aligned to [`TENSOR_ALIGNMENT`][tensor::TENSOR_ALIGNMENT] bytes,
+    /// which is at least as large as the input `Tensor`'s data.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the given index is not a valid input tensor index in
+    /// [0, [`Interpreter::input_tensor_count()`]), `buffer` is misaligned or smaller than the
+    /// input tensor, or TensorFlow Lite C fails to bind it. The tensor keeps its previous
+    /// memory then.
+    pub fn set_input_allocation<T: tensor::TensorElement>(
+        &mut self,
+        index: usize,
+        buffer: &'b mut [T],
+    ) -> Result<()> {
+        let max_index = self.interpreter.input_tensor_count() - 1;
+        if index > max_index {
+            return Err(Error::new(ErrorKind::InvalidTensorIndex(index, max_index)));
+        }
+        let tensor_index = unsafe {
+            TfLiteInterpreterGetInputTensorIndex(self.interpreter.interpreter_ptr, index as i32)
+        };
+        let buffer = self.bind(index, tensor_index, buffer)?;
+        self.inputs.insert(index, buffer);
+        Ok(())
+    }
+
+    /// Binds the caller-owned `buffer` as memory of the output [`Tensor`] at the given
+    /// `index`, replacing any buffer bound to it before.
+    ///
+    /// The interpreter writes the output directly to `buffer` instead of its internal arena.
+    ///
+    /// # Arguments
+    ///
+    /// * `index`: The index for the output [`Tensor`].
+    /// * `buffer`: The buffer aligned to [`TENSOR_ALIGNMENT`][tensor::TENSOR_ALIGNMENT] bytes,
+    /// which is at least as large as the output `Tensor`'s data.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the given index is not a valid output tensor index in
+    /// [0, [`Interpreter::output_tensor_count()`]), `buffer` is misaligned or smaller than the
+    /// output tensor, or TensorFlow Lite C fails to bind it. The tensor keeps its previous
+    /// memory then.
+    pub fn set_output_allocation<T: tensor::TensorElement>(
+        &mut self,
+        index: usize,
+        buffer: &'b mut [T],
+    ) -> Result<()> {
+        let max_index = self.interpreter.output_tensor_count() - 1;
+        if index > max_index {
+            return Err(Error::new(ErrorKind::InvalidTensorIndex(index, max_index)));
+        }
+        let tensor_index = unsafe {
+            TfLiteInterpreterGetOutputTensorIndex(self.interpreter.interpreter_ptr, index as i32)
+        };
+        let buffer = self.bind(index, tensor_index, buffer)?;
+        self.outputs.insert(index, buffer);
+        Ok(())
+    }
+
+    /// Allocates memory for all tensors, see [`Interpreter::allocate_tensors()`].
+    ///
+    /// It is called by [`CustomAllocations::invoke()`] if a buffer was bound since the last
+    /// allocation.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if TensorFlow Lite C fails to allocate memory for the tensors.
+    pub fn allocate_tensors(&mut self) -> Result<()> {
+        self.interpreter.allocate_tensors()?;
+        self.needs_allocation = false;
+        Ok(())
+    }
+
+    /// Invokes the interpreter, which reads from and writes to the bound buffers, see
+    /// [`Interpreter::invoke()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error if TensorFlow Lite C fails to allocate the tensors or to invoke, the
+    /// invocation is cancelled, or a tensor whose buffer was released by a previous guard has
+    /// no buffer bound by this one.
+    pub fn invoke(&mut self) -> Result<()> {
+        if self.needs_allocation {
+            self.allocate_tensors()?;
+        }
+        self.interpreter.invoke()
+    }
+
+    /// Returns the buffer bound to the input at the given `index` as a slice of `T`s, if any.
+    pub fn input<T: tensor::TensorElement>(&self, index: usize) -> Option<&[T]> {
+        self.inputs.get(&index).map(|buffer| unsafe {
+            std::slice::from_raw_parts(
+                buffer.data as *const T,
+                buffer.bytes / std::mem::size_of::<T>(),
+            )
+        })
+    }
+
+    /// Returns the buffer bound to the input at the given `index` as a mutable slice of `T`s,
+    /// if any.
+    pub fn input_mut<T: tensor::TensorElement>(&mut self, index: usize) -> Option<&mut [T]> {
+        self.inputs.get(&index).map(|buffer| unsafe {
+            std::slice::from_raw_parts_mut(
+                buffer.data as *mut T,
+                buffer.bytes / std::mem::size_of::<T>(),
+            )
+        })
+    }
+
+    /// Returns the buffer bound to the output at the given `index` as a slice of `T`s, if any.
+    pub fn output<T: tensor::TensorElement>(&self, index: usize) -> Option<&[T]> {
+        self.outputs.get(&index).map(|buffer| unsafe {
+            std::slice::from_raw_parts(
+                buffer.data as *const T,
+                buffer.bytes / std::mem::size_of::<T>(),
+            )
+        })
+    }
+
+    /// Binds `buffer` to the tensor at `tensor_index` among all tensors, which is the input or
+    /// output at `index`.
+    fn bind<T: tensor::TensorElement>(
+        &mut self,
+        index: usize,
+        tensor_index: i32,
+        buffer: &'b mut [T],
+    ) -> Result<BoundBuffer> {
+        let data = buffer.as_mut_ptr() as *mut u8;
+        let bytes = std::mem::size_of_val(buffer);
+        // The buffer is borrowed for 'b, and the tensor is marked as released when the guard
+        // is dropped.
+        unsafe {
+            self.interpreter
+                .set_custom_allocation(tensor_index, data, bytes)
+        }
+        .map_err(|error| {
+            if let ErrorKind::FailedToSetCustomAllocation(_) = error.kind() {
+                Error::new(ErrorKind::FailedToSetCustomAllocation(index))
+            } else {
+                error
+            }
+        })?;
+        self.interpreter.released_allocations.remove(&tensor_index);
+        self.needs_allocation = true;
+        Ok(BoundBuffer {
+            tensor_index,
+            data,
+            bytes,
+        })
+    }
+}
+
+impl Drop for CustomAllocations<'_, '_, '_> {
+    fn drop(&mut self) {
+        let tensor_indices = self
+            .inputs
+            .values()
+            .chain(self.outputs.values())
+            .map(|buffer| buffer.tensor_index);
+        self.interpreter.released_allocations.extend(tensor_indices);
+    }
+}
+
+/// Checks that `bytes` bytes at `data` are aligned and large enough to be the custom allocation
+/// of the tensor at `tensor_ptr`.
+unsafe fn check_custom_allocation(
+    tensor_ptr: *const TfLiteTensor,
+    data: *const u8,
+    bytes: usize,
+) -> Result<()> {
+    if data as usize % tensor::TENSOR_ALIGNMENT != 0 {
+        return Err(Error::new(ErrorKind::MisalignedTensorBuffer(
+            tensor::TENSOR_ALIGNMENT,
+        )));
+    }
+    let byte_count = TfLiteTensorByteSize(tensor_ptr);
+    if bytes < byte_count {
+        return Err(Error::new(ErrorKind::InvalidTensorDataCount(
+            bytes, byte_count,
+        )));
+    }
+    Ok(())
+}
+
+/// The type of `va_list` arguments in C function pointers, as generated by bindgen.
+#[cfg(all(target_arch = "x86_64", not(target_os = "windows")))]
+pub(crate) type VaListArg = *mut __va_list_tag;
+#[cfg(not(all(target_arch = "x86_64", not(target_os = "windows"))))]
+pub(crate) type VaListArg = va_list;
+
+extern "C" {
+    fn vsnprintf(s: *mut c_char, n: usize, format: *const c_char, args: VaListArg) -> c_int;
+}
+
+/// Formats the error message and forwards it to the [`ErrorReporter`] at `user_data`.
+unsafe extern "C" fn report_error(user_data: *mut c_void, format: *const c_char, args: VaListArg) {
+    let reporter = &*(user_data as *const ErrorReporter);
+    let mut buffer = [0 as c_char; 1024];
+    if vsnprintf(buffer.as_mut_ptr(), buffer.len(), format, args) < 0 {
+        return;
+    }
+    let message = CStr::from_ptr(buffer.as_ptr()).to_string_lossy();
+    crate::catch_unwind((), || reporter(&message));
+}
+
+/// Returns the name of the tensor at `tensor_ptr`, which is available before its memory is
+/// allocated.
+unsafe fn tensor_name(tensor_ptr: *const TfLiteTensor) -> String {
+    if tensor_ptr.is_null() {
+        return String::new();
+    }
+    let name_ptr = TfLiteTensorName(tensor_ptr);
+    if name_ptr.is_null() {
+        String::new()
+    } else {
+        CStr::from_ptr(name_ptr).to_string_lossy().into_owned()
+    }
+}
+
 impl Drop for Interpreter<'_> {
     fn drop(&mut self) {
         unsafe {
+            if let Some(cancellation) = self.cancellation.as_ref() {
+                cancellation.lock().unwrap().take();
+            }
             TfLiteInterpreterDelete(self.interpreter_ptr);
 
             #[cfg(feature = "xnnpack")]
@@ -389,6 +2017,14 @@ mod tests {
     const MODEL_PATH: &str = "tests\\add.bin";
     #[cfg(not(target_os = "windows"))]
     const MODEL_PATH: &str = "tests/add.bin";
+    #[cfg(target_os = "windows")]
+    const STATEFUL_MODEL_PATH: &str = "tests\\stateful.bin";
+    #[cfg(not(target_os = "windows"))]
+    const STATEFUL_MODEL_PATH: &str = "tests/stateful.bin";
+    #[cfg(all(feature = "xnnpack", target_os = "windows"))]
+    const FULLY_CONNECTED_MODEL_PATH: &str = "tests\\fully_connected.bin";
+    #[cfg(all(feature = "xnnpack", not(target_os = "windows")))]
+    const FULLY_CONNECTED_MODEL_PATH: &str = "tests/fully_connected.bin";
 
     #[test]
     fn test_interpreter_input_output_count() {
@@ -456,6 +2092,74 @@ mod tests {
         assert_eq!(data, tensor.data());
     }
 
+    #[test]
+    fn test_interpreter_tensors_by_name() {
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let interpreter = Interpreter::new(&model, None).expect("Cannot create interpreter!");
+        let input_name = interpreter.input_names().remove(0);
+        let output_name = interpreter.output_names().remove(0);
+        assert_eq!(interpreter.input_index(&input_name), Ok(0));
+        assert_eq!(interpreter.output_index(&output_name), Ok(0));
+
+        let err = interpreter.input_by_name("missing").err().unwrap();
+        assert_eq!(ErrorKind::UnknownTensorName, err.kind());
+        assert_eq!(Some("missing"), err.requested_name());
+        assert_eq!(vec![input_name.clone()], err.available_names());
+        assert!(err.to_string().contains(&input_name));
+
+        interpreter
+            .allocate_tensors()
+            .expect("Cannot allocate tensors");
+        let data = (0..192).map(|x| x as f32).collect::<Vec<f32>>();
+        assert!(interpreter.copy_by_name(&data[..], &input_name).is_ok());
+        assert_eq!(
+            data,
+            interpreter
+                .input_by_name(&input_name)
+                .unwrap()
+                .data::<f32>()
+        );
+        assert!(interpreter.invoke().is_ok());
+        let expected: Vec<f32> = data.iter().map(|e| e * 3.0).collect();
+        assert_eq!(
+            expected,
+            interpreter
+                .output_by_name(&output_name)
+                .unwrap()
+                .data::<f32>()
+        );
+    }
+
+    #[test]
+    fn test_interpreter_tensor_details() {
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let interpreter = Interpreter::new(&model, None).expect("Cannot create interpreter!");
+        assert_eq!(
+            ErrorKind::AllocateTensorsRequired,
+            interpreter.input(0).err().unwrap().kind()
+        );
+
+        let inputs = interpreter.input_details().unwrap();
+        assert_eq!(inputs.len(), 1);
+        assert_eq!(inputs[0].data_type(), tensor::DataType::Float32);
+        assert_eq!(inputs[0].shape().dimensions(), &vec![1, 8, 8, 3]);
+        assert_eq!(inputs[0].shape_signature().len(), 4);
+        assert_eq!(inputs[0].quantization_parameters(), None);
+        let outputs = interpreter.output_details().unwrap();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].data_type(), tensor::DataType::Float32);
+
+        interpreter
+            .resize_input(0, tensor::Shape::new(vec![2, 8, 8, 3]))
+            .expect("Resize failed");
+        let inputs = interpreter.input_details().unwrap();
+        assert_eq!(inputs[0].shape().dimensions(), &vec![2, 8, 8, 3]);
+        interpreter
+            .allocate_tensors()
+            .expect("Cannot allocate tensors");
+        assert_eq!(inputs[0].name(), interpreter.input(0).unwrap().name());
+    }
+
     #[test]
     fn test_interpreter_invoke() {
         let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
@@ -478,6 +2182,316 @@ mod tests {
         assert_eq!(expected, output_vector);
     }
 
+    #[test]
+    fn test_interpreter_all_tensors() {
+        use crate::interpreter::Options;
+        let options = Some(Options {
+            preserve_all_tensors: true,
+            ..Options::default()
+        });
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let interpreter = Interpreter::new(&model, options).expect("Cannot create interpreter!");
+        interpreter
+            .allocate_tensors()
+            .expect("Cannot allocate tensors");
+
+        let count = interpreter.tensor_count();
+        assert!(count > interpreter.input_tensor_count() + interpreter.output_tensor_count());
+        let err = interpreter.tensor(count).err().unwrap();
+        assert_eq!(ErrorKind::InvalidTensorIndex(count, count - 1), err.kind());
+
+        let data = (0..192).map(|x| x as f32).collect::<Vec<f32>>();
+        assert!(interpreter.copy(&data[..], 0).is_ok());
+        assert!(interpreter.invoke().is_ok());
+
+        let tensors = interpreter
+            .tensors()
+            .collect::<crate::Result<Vec<_>>>()
+            .expect("Cannot read tensors");
+        assert_eq!(tensors.len(), count);
+        let input_name = interpreter.input(0).unwrap().name().to_owned();
+        let output_name = interpreter.output(0).unwrap().name().to_owned();
+        let expected: Vec<f32> = data.iter().map(|e| e * 3.0).collect();
+        for tensor in tensors.iter() {
+            if tensor.name() == input_name {
+                assert_eq!(data, tensor.data::<f32>());
+            } else if tensor.name() == output_name {
+                assert_eq!(expected, tensor.data::<f32>());
+            }
+        }
+    }
+
+    #[test]
+    fn test_interpreter_options_builder() {
+        use crate::interpreter::Options;
+        let err = Options::builder().thread_count(-2).build().err().unwrap();
+        assert_eq!(ErrorKind::InvalidThreadCount(-2), err.kind());
+
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let options = Options {
+            thread_count: -5,
+            ..Options::default()
+        };
+        let err = Interpreter::new(&model, Some(options)).err().unwrap();
+        assert_eq!(ErrorKind::InvalidThreadCount(-5), err.kind());
+
+        let options = Options::builder()
+            .thread_count(1)
+            .enable_delegate_fallback(true)
+            .error_reporter(|message| eprintln!("{}", message))
+            .build()
+            .expect("Cannot build options");
+        assert!(options.is_delegate_fallback_enabled());
+        assert!(!options.is_cancellation_enabled());
+        let interpreter =
+            Interpreter::new(&model, Some(options)).expect("Cannot create interpreter!");
+        assert!(interpreter.cancellation_token().is_none());
+        let report = interpreter.delegation_report();
+        assert!(!report.is_fallback());
+        assert_eq!(report.delegated_node_count(), 0);
+        interpreter
+            .allocate_tensors()
+            .expect("Cannot allocate tensors");
+        assert!(interpreter.invoke().is_ok());
+    }
+
+    #[test]
+    fn test_interpreter_thread_pool() {
+        use crate::interpreter::Options;
+        use crate::thread_pool::ThreadPool;
+        use std::sync::Arc;
+
+        let pool = ThreadPool::new(2).unwrap();
+        let options = Options::builder()
+            .thread_count(1)
+            .thread_pool(pool.clone())
+            .build()
+            .expect("Cannot build options");
+        let model = Arc::new(Model::new(MODEL_PATH).expect("Cannot load model from file!"));
+        let handles = (0..4)
+            .map(|_| {
+                let model = model.clone();
+                let options = options.clone();
+                std::thread::spawn(move || {
+                    let interpreter = Interpreter::new(&model, Some(options))
+                        .expect("Cannot create interpreter!");
+                    interpreter
+                        .allocate_tensors()
+                        .expect("Cannot allocate tensors");
+                    let data = (0..192).map(|x| x as f32).collect::<Vec<f32>>();
+                    for _ in 0..10 {
+                        assert!(interpreter.copy(&data[..], 0).is_ok());
+                        assert!(interpreter.invoke().is_ok());
+                        let expected: Vec<f32> = data.iter().map(|e| e * 3.0).collect();
+                        assert_eq!(expected, interpreter.output(0).unwrap().data::<f32>());
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(pool.available_thread_count(), 2);
+    }
+
+    #[test]
+    fn test_interpreter_cancellation() {
+        use crate::interpreter::{CancellationToken, Options};
+        use crate::profiler::TelemetryProfiler;
+        use std::sync::{Arc, Mutex};
+
+        /// Cancels the invocation when its first operator begins, so the second is not run.
+        #[derive(Default)]
+        struct CancellingProfiler(Mutex<Option<CancellationToken>>);
+
+        impl TelemetryProfiler for CancellingProfiler {
+            fn begin_op_invoke(&self, _op_name: &str, op_index: i64, _subgraph_index: i64) -> u32 {
+                if op_index == 0 {
+                    if let Some(token) = self.0.lock().unwrap().as_ref() {
+                        token.cancel();
+                    }
+                }
+                0
+            }
+        }
+
+        let profiler = Arc::new(CancellingProfiler::default());
+        let options = Options::builder()
+            .enable_cancellation(true)
+            .telemetry_profiler(profiler.clone())
+            .build()
+            .expect("Cannot build options");
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let interpreter =
+            Interpreter::new(&model, Some(options)).expect("Cannot create interpreter!");
+        interpreter
+            .allocate_tensors()
+            .expect("Cannot allocate tensors");
+        interpreter.copy(&[1.0f32; 8 * 8 * 3], 0).unwrap();
+
+        *profiler.0.lock().unwrap() = interpreter.cancellation_token();
+        let err = interpreter.invoke().unwrap_err();
+        assert_eq!(ErrorKind::InvokeCancelled, err.kind());
+
+        // A cancellation only affects the ongoing invocation.
+        *profiler.0.lock().unwrap() = None;
+        interpreter.invoke().unwrap();
+        assert_eq!(
+            interpreter.output(0).unwrap().data::<f32>(),
+            &[3.0f32; 8 * 8 * 3]
+        );
+
+        let token = interpreter.cancellation_token().unwrap();
+        drop(interpreter);
+        // Cancelling after the interpreter is dropped has no effect.
+        token.cancel();
+    }
+
+    #[test]
+    fn test_interpreter_variable_tensors() {
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let interpreter = Interpreter::new(&model, None).expect("Cannot create interpreter!");
+        interpreter
+            .allocate_tensors()
+            .expect("Cannot allocate tensors");
+
+        // The model is stateless, so it has no variable tensors.
+        assert_eq!(interpreter.variable_tensor_count(), 0);
+        let err = interpreter.variable(0).err().unwrap();
+        assert_eq!(ErrorKind::InvalidTensorIndex(0, 0), err.kind());
+        assert!(interpreter.reset_variable_tensors().is_ok());
+
+        // The model accumulates its inputs in a variable tensor.
+        let model = Model::new(STATEFUL_MODEL_PATH).expect("Cannot load model from file!");
+        let interpreter = Interpreter::new(&model, None).expect("Cannot create interpreter!");
+        interpreter
+            .allocate_tensors()
+            .expect("Cannot allocate tensors");
+        assert_eq!(interpreter.variable_tensor_count(), 1);
+        assert_eq!(interpreter.variable(0).unwrap().data::<f32>(), &[0.0]);
+        for expected in [1.0f32, 2.0, 3.0].iter() {
+            interpreter.copy(&[1.0f32], 0).unwrap();
+            interpreter.invoke().unwrap();
+            assert_eq!(interpreter.output(0).unwrap().data::<f32>(), &[*expected]);
+        }
+        assert_eq!(interpreter.variable(0).unwrap().data::<f32>(), &[3.0]);
+
+        interpreter.reset_variable_tensors().unwrap();
+        assert_eq!(interpreter.variable(0).unwrap().data::<f32>(), &[0.0]);
+        interpreter.invoke().unwrap();
+        assert_eq!(interpreter.output(0).unwrap().data::<f32>(), &[1.0]);
+
+        // Restoring a saved state continues the stream from it.
+        interpreter
+            .variable(0)
+            .unwrap()
+            .set_data(&[10.0f32])
+            .unwrap();
+        interpreter.invoke().unwrap();
+        assert_eq!(interpreter.output(0).unwrap().data::<f32>(), &[11.0]);
+    }
+
+    #[test]
+    fn test_interpreter_custom_allocation() {
+        #[repr(C, align(64))]
+        struct AlignedBuffer([f32; 192]);
+
+        let mut input_buffer = AlignedBuffer([0.0; 192]);
+        let mut output_buffer = AlignedBuffer([0.0; 192]);
+        let mut misaligned_buffer = AlignedBuffer([0.0; 192]);
+        let mut small_buffer = AlignedBuffer([0.0; 192]);
+        let mut second_output_buffer = AlignedBuffer([0.0; 192]);
+        let data = (0..192).map(|x| x as f32).collect::<Vec<f32>>();
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let mut interpreter = Interpreter::new(&model, None).expect("Cannot create interpreter!");
+        interpreter
+            .allocate_tensors()
+            .expect("Cannot allocate tensors");
+        {
+            let mut allocations = interpreter.custom_allocations();
+            let err = allocations
+                .set_input_allocation(0, &mut misaligned_buffer.0[1..])
+                .err()
+                .unwrap();
+            assert_eq!(
+                ErrorKind::MisalignedTensorBuffer(tensor::TENSOR_ALIGNMENT),
+                err.kind()
+            );
+            let err = allocations
+                .set_input_allocation(0, &mut small_buffer.0[..64])
+                .err()
+                .unwrap();
+            assert_eq!(ErrorKind::InvalidTensorDataCount(256, 768), err.kind());
+
+            allocations
+                .set_input_allocation(0, &mut input_buffer.0[..])
+                .expect("Cannot set input allocation");
+            allocations
+                .set_output_allocation(0, &mut output_buffer.0[..])
+                .expect("Cannot set output allocation");
+            assert!(allocations.input::<f32>(1).is_none());
+            allocations
+                .input_mut::<f32>(0)
+                .unwrap()
+                .copy_from_slice(&data);
+            assert!(allocations.invoke().is_ok());
+            let expected: Vec<f32> = data.iter().map(|e| e * 3.0).collect();
+            assert_eq!(allocations.output::<f32>(0).unwrap(), expected.as_slice());
+
+            allocations.input_mut::<f32>(0).unwrap()[0] = 1.0;
+            assert!(allocations.invoke().is_ok());
+            assert_eq!(allocations.output::<f32>(0).unwrap()[0], 3.0);
+        }
+        assert_eq!(input_buffer.0[0], 1.0);
+        assert_eq!(output_buffer.0[0], 3.0);
+
+        // The interpreter keeps the addresses of the released buffers, so it refuses to use them.
+        let input_index = unsafe {
+            crate::bindings::TfLiteInterpreterGetInputTensorIndex(interpreter.interpreter_ptr, 0)
+                as usize
+        };
+        let err = interpreter.invoke().unwrap_err();
+        assert_eq!(ErrorKind::CustomAllocationReleased(input_index), err.kind());
+        let err = interpreter.input(0).unwrap_err();
+        assert_eq!(ErrorKind::CustomAllocationReleased(input_index), err.kind());
+        assert!(interpreter.copy(&[2.0f32; 192], 0).is_err());
+        assert!(interpreter.output(0).is_err());
+
+        {
+            let mut allocations = interpreter.custom_allocations();
+            allocations
+                .set_input_allocation(0, &mut input_buffer.0[..])
+                .expect("Cannot set input allocation");
+            let err = allocations.invoke().unwrap_err();
+            assert!(matches!(
+                err.kind(),
+                ErrorKind::CustomAllocationReleased(index) if index != input_index
+            ));
+            allocations
+                .set_output_allocation(0, &mut second_output_buffer.0[..])
+                .expect("Cannot set output allocation");
+            allocations.input_mut::<f32>(0).unwrap().fill(2.0);
+            assert!(allocations.invoke().is_ok());
+        }
+        assert_eq!(second_output_buffer.0, [6.0f32; 192]);
+        assert_eq!(output_buffer.0[1], 3.0);
+
+        // Buffers which failed to be bound are not released.
+        let mut interpreter = Interpreter::new(&model, None).expect("Cannot create interpreter!");
+        interpreter
+            .allocate_tensors()
+            .expect("Cannot allocate tensors");
+        {
+            let mut allocations = interpreter.custom_allocations();
+            assert!(allocations
+                .set_input_allocation(0, &mut small_buffer.0[..64])
+                .is_err());
+        }
+        interpreter.copy(&[2.0f32; 192], 0).unwrap();
+        interpreter.invoke().unwrap();
+        assert_eq!(interpreter.output(0).unwrap().data::<f32>(), &[6.0f32; 192]);
+    }
+
     #[cfg(feature = "xnnpack")]
     #[test]
     fn test_interpreter_invoke_xnnpack() {
@@ -485,6 +2499,7 @@ mod tests {
         let options = Some(Options {
             thread_count: 2,
             is_xnnpack_enabled: true,
+            ..Options::default()
         });
         let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
         let interpreter = Interpreter::new(&model, options).expect("Cannot create interpreter!");
@@ -505,4 +2520,148 @@ mod tests {
         let output_vector = output_tensor.data::<f32>().to_vec();
         assert_eq!(expected, output_vector);
     }
+
+    #[cfg(feature = "xnnpack")]
+    #[test]
+    fn test_interpreter_xnnpack_weight_cache() {
+        use crate::interpreter::{Options, WeightCache, WeightCacheInner};
+        // The weights of the fully connected layer are packed by XNNPACK.
+        let model = Model::new(FULLY_CONNECTED_MODEL_PATH).expect("Cannot load model from file!");
+        let cache_path = std::env::temp_dir().join(format!(
+            "tflitec_test_weight_cache_{}.xnn",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&cache_path);
+        let weight_caches = vec![
+            (
+                WeightCache::new().expect("Cannot create weight cache"),
+                false,
+            ),
+            (
+                WeightCache::from_file(&cache_path).expect("Cannot create weight cache"),
+                true,
+            ),
+        ];
+        for (weight_cache, is_file) in weight_caches {
+            let options = Options::builder()
+                .xnnpack_weight_cache(weight_cache.clone())
+                .build()
+                .expect("Cannot build options");
+            assert!(options.is_xnnpack_enabled);
+            let data = [1.0f32, 2.0, 3.0, 4.0];
+            let expected = [3.0f32, 6.0, 9.0, 12.0];
+            let mut packed_weights = None;
+            // Interpreters created after the first one reuse the packed weights.
+            for index in 0..3 {
+                let interpreter = Interpreter::new(&model, Some(options.clone()))
+                    .expect("Cannot create interpreter!");
+                if let WeightCacheInner::InMemory {
+                    cache_ptr,
+                    is_finalized,
+                } = weight_cache.inner.as_ref()
+                {
+                    if index == 0 {
+                        // A hard finalized cache cannot pack new weights, so the interpreters
+                        // created later are only created if they reuse the packed weights.
+                        let mut is_finalized = is_finalized.lock().unwrap();
+                        assert!(unsafe {
+                            crate::bindings::TfLiteXNNPackDelegateWeightsCacheFinalizeHard(
+                                *cache_ptr,
+                            )
+                        });
+                        *is_finalized = true;
+                    }
+                }
+                interpreter
+                    .allocate_tensors()
+                    .expect("Cannot allocate tensors");
+                assert!(interpreter.copy(&data[..], 0).is_ok());
+                assert!(interpreter.invoke().is_ok());
+                assert_eq!(&expected, interpreter.output(0).unwrap().data::<f32>());
+                if is_file {
+                    // The file written by the first interpreter is loaded, not rewritten.
+                    let modified = std::fs::metadata(&cache_path)
+                        .and_then(|metadata| metadata.modified())
+                        .expect("Cannot read weight cache file");
+                    let contents = std::fs::read(&cache_path).unwrap();
+                    assert!(!contents.is_empty());
+                    let (first_contents, first_modified) = packed_weights
+                        .get_or_insert_with(|| (contents.clone(), modified))
+                        .clone();
+                    assert_eq!(first_contents, contents);
+                    assert_eq!(first_modified, modified);
+                }
+            }
+        }
+        let _ = std::fs::remove_file(&cache_path);
+    }
+
+    #[cfg(feature = "xnnpack")]
+    #[test]
+    fn test_interpreter_invoke_xnnpack_options() {
+        use crate::bindings::*;
+        use crate::interpreter::{Options, XnnpackOptions};
+        let xnnpack_options = XnnpackOptions {
+            dynamic_fully_connected: true,
+            transient_indirection_buffer: true,
+            handle_variable_ops: true,
+            ..XnnpackOptions::default()
+        };
+        let options = Options::builder()
+            .thread_count(2)
+            .xnnpack_options(xnnpack_options)
+            .build()
+            .expect("Cannot build options");
+        assert!(options.is_xnnpack_enabled);
+        assert_eq!(options.xnnpack_options(), &xnnpack_options);
+
+        // The defaults keep the flags of the TensorFlow Lite build, and `false` clears a flag.
+        let default_flags = unsafe { TfLiteXNNPackDelegateOptionsDefault() }.flags;
+        assert_eq!(
+            XnnpackOptions::default().apply(default_flags),
+            default_flags
+        );
+        let without_qs8 = XnnpackOptions {
+            enable_qs8: false,
+            ..XnnpackOptions::default()
+        };
+        let flags = without_qs8.apply(default_flags | TFLITE_XNNPACK_DELEGATE_FLAG_QS8);
+        assert_eq!(flags & TFLITE_XNNPACK_DELEGATE_FLAG_QS8, 0);
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let interpreter =
+            Interpreter::new(&model, Some(options)).expect("Cannot create interpreter!");
+        interpreter
+            .allocate_tensors()
+            .expect("Cannot allocate tensors");
+
+        let data = (0..192).map(|x| x as f32).collect::<Vec<f32>>();
+        assert!(interpreter.copy(&data[..], 0).is_ok());
+        assert!(interpreter.invoke().is_ok());
+        let expected: Vec<f32> = data.iter().map(|e| e * 3.0).collect();
+        let output_tensor = interpreter.output(0).unwrap();
+        assert_eq!(expected, output_tensor.data::<f32>());
+    }
+
+    #[test]
+    #[cfg(feature = "xnnpack")]
+    fn test_interpreter_delegation_report_xnnpack() {
+        use crate::interpreter::Options;
+        let options = Options::builder()
+            .xnnpack_enabled(true)
+            .enable_delegate_fallback(true)
+            .build()
+            .expect("Cannot build options");
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let interpreter =
+            Interpreter::new(&model, Some(options)).expect("Cannot create interpreter!");
+        let report = interpreter.delegation_report();
+        assert!(!report.is_fallback());
+        assert_eq!(report.delegates().len(), 1);
+        let xnnpack = &report.delegates()[0];
+        assert_eq!(xnnpack.name, "XNNPACK");
+        assert!(xnnpack.is_applied);
+        assert!(!xnnpack.is_failed);
+        assert!(xnnpack.node_count.unwrap() > 0);
+        assert_eq!(report.delegated_node_count(), xnnpack.node_count.unwrap());
+    }
 }
@@ -3,10 +3,28 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub mod async_interpreter;
+pub mod batching;
+pub mod cache;
+pub mod delegate;
 mod error;
 pub mod interpreter;
+pub mod lifecycle;
+#[cfg(any(feature = "tracing", feature = "log"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "tracing", feature = "log"))))]
+pub mod logging;
+pub mod metrics;
 pub mod model;
+pub mod pool;
+pub mod profiler;
+pub mod signature;
 pub mod tensor;
+pub mod thread_pool;
+pub mod trace;
+pub mod validator;
+mod worker;
 
 pub(crate) mod bindings {
     #![allow(clippy::all)]
@@ -19,3 +37,11 @@ pub(crate) mod bindings {
 }
 
 pub use self::error::{Error, ErrorKind, Result};
+
+/// Runs `f` and returns its result, or `fallback` if it panics.
+///
+/// Callbacks of TensorFlow Lite C run user code through it, since unwinding into C is
+/// undefined behavior.
+pub(crate) fn catch_unwind<R>(fallback: R, f: impl FnOnce() -> R) -> R {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_or(fallback)
+}
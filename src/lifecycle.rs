@@ -0,0 +1,306 @@
+//! A typestate API of the [`Interpreter`] lifecycle, which rejects invalid calls at compile
+//! time instead of failing with [`ErrorKind::AllocateTensorsRequired`] or
+//! [`ErrorKind::InvokeInterpreterRequired`].
+//!
+//! A [`TypedInterpreter`] is created [`Unallocated`]. [`TypedInterpreter::allocate()`] consumes
+//! it and returns an [`Allocated`] one whose inputs can be copied, and
+//! [`TypedInterpreter::invoke()`] returns an [`Invoked`] one whose outputs can be read.
+//! Resizing an input moves it back to [`Unallocated`].
+//!
+//! # Examples
+//!
+//! ```
+//! use tflitec::lifecycle::TypedInterpreter;
+//! use tflitec::model::Model;
+//! use tflitec::tensor::Shape;
+//!
+//! let model = Model::new("tests/add.bin")?;
+//! let interpreter = TypedInterpreter::new(&model, None)?
+//!     .resize_input(0, Shape::new(vec![2, 8, 8, 3]))?
+//!     .allocate()?;
+//! interpreter.copy(&[1.0f32; 2 * 8 * 8 * 3], 0)?;
+//! let interpreter = interpreter.invoke()?;
+//! assert_eq!(interpreter.output(0)?.data::<f32>()[0], 3.0);
+//! # Ok::<(), tflitec::Error>(())
+//! ```
+//!
+//! Copying inputs before allocating tensors does not compile:
+//!
+//! ```compile_fail
+//! # use tflitec::lifecycle::TypedInterpreter;
+//! # use tflitec::model::Model;
+//! let model = Model::new("tests/add.bin")?;
+//! let interpreter = TypedInterpreter::new(&model, None)?;
+//! interpreter.copy(&[1.0f32; 8 * 8 * 3], 0)?;
+//! # Ok::<(), tflitec::Error>(())
+//! ```
+//!
+//! Neither does reading outputs before invoking:
+//!
+//! ```compile_fail
+//! # use tflitec::lifecycle::TypedInterpreter;
+//! # use tflitec::model::Model;
+//! let model = Model::new("tests/add.bin")?;
+//! let interpreter = TypedInterpreter::new(&model, None)?.allocate()?;
+//! interpreter.output(0)?;
+//! # Ok::<(), tflitec::Error>(())
+//! ```
+use std::fmt::{Debug, Display, Formatter};
+use std::marker::PhantomData;
+
+use crate::interpreter::{Interpreter, Options};
+use crate::model::Model;
+use crate::tensor::{Shape, Tensor};
+use crate::{Error, Result};
+
+/// The state of a [`TypedInterpreter`] whose tensors are not allocated.
+#[derive(Debug)]
+pub enum Unallocated {}
+
+/// The state of a [`TypedInterpreter`] whose tensors are allocated.
+#[derive(Debug)]
+pub enum Allocated {}
+
+/// The state of a [`TypedInterpreter`] which is invoked after its tensors were allocated.
+#[derive(Debug)]
+pub enum Invoked {}
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for super::Allocated {}
+    impl Sealed for super::Invoked {}
+}
+
+/// The states of a [`TypedInterpreter`] whose tensors are allocated, [`Allocated`] and
+/// [`Invoked`].
+pub trait Ready: sealed::Sealed {}
+
+impl Ready for Allocated {}
+impl Ready for Invoked {}
+
+/// An [`Interpreter`] whose lifecycle state `S` is tracked in its type, see the
+/// [module documentation][crate::lifecycle].
+pub struct TypedInterpreter<'a, S> {
+    interpreter: Interpreter<'a>,
+    state: PhantomData<S>,
+}
+
+impl<S> Debug for TypedInterpreter<'_, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TypedInterpreter")
+            .field("interpreter", &self.interpreter)
+            .field("state", &std::any::type_name::<S>())
+            .finish()
+    }
+}
+
+impl<'a> TypedInterpreter<'a, Unallocated> {
+    /// Creates a new [`Unallocated`] interpreter, see [`Interpreter::new()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the [`Interpreter`] cannot be created.
+    pub fn new(model: &'a Model<'a>, options: Option<Options>) -> Result<Self> {
+        Ok(TypedInterpreter::from_interpreter(Interpreter::new(
+            model, options,
+        )?))
+    }
+
+    /// Allocates memory for all tensors, see [`Interpreter::allocate_tensors()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error with the interpreter if TensorFlow Lite C fails to allocate the tensors.
+    pub fn allocate(
+        self,
+    ) -> std::result::Result<TypedInterpreter<'a, Allocated>, StateError<Self>> {
+        match self.interpreter.allocate_tensors() {
+            Ok(()) => Ok(self.transition()),
+            Err(error) => Err(StateError::new(error, self)),
+        }
+    }
+}
+
+impl<'a, S> TypedInterpreter<'a, S> {
+    fn from_interpreter(interpreter: Interpreter<'a>) -> Self {
+        TypedInterpreter {
+            interpreter,
+            state: PhantomData,
+        }
+    }
+
+    fn transition<T>(self) -> TypedInterpreter<'a, T> {
+        TypedInterpreter::from_interpreter(self.interpreter)
+    }
+
+    /// Resizes the input [`Tensor`] at the given `index` to `shape`, which requires allocating
+    /// tensors again, see [`Interpreter::resize_input()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error with the interpreter if given index is not a valid input tensor index or
+    /// TensorFlow Lite C fails internally.
+    pub fn resize_input(
+        self,
+        index: usize,
+        shape: Shape,
+    ) -> std::result::Result<TypedInterpreter<'a, Unallocated>, StateError<Self>> {
+        match self.interpreter.resize_input(index, shape) {
+            Ok(()) => Ok(self.transition()),
+            Err(error) => Err(StateError::new(error, self)),
+        }
+    }
+
+    /// Returns the total number of input [`Tensor`]s associated with the model.
+    pub fn input_tensor_count(&self) -> usize {
+        self.interpreter.input_tensor_count()
+    }
+
+    /// Returns the total number of output [`Tensor`]s associated with the model.
+    pub fn output_tensor_count(&self) -> usize {
+        self.interpreter.output_tensor_count()
+    }
+
+    /// Returns the underlying [`Interpreter`], whose state is not tracked anymore.
+    pub fn into_inner(self) -> Interpreter<'a> {
+        self.interpreter
+    }
+}
+
+impl<'a, S: Ready> TypedInterpreter<'a, S> {
+    /// Returns the input [`Tensor`] at the given `index`, see [`Interpreter::input()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error if given index is not a valid input tensor index.
+    pub fn input(&self, index: usize) -> Result<Tensor> {
+        self.interpreter.input(index)
+    }
+
+    /// Copies the given `data` to the input [`Tensor`] at the given `index`, see
+    /// [`Interpreter::copy()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error if byte count of the data does not match the buffer size of the input
+    /// tensor or the given index is not a valid input tensor index.
+    pub fn copy<T>(&self, data: &[T], index: usize) -> Result<()> {
+        self.interpreter.copy(data, index)
+    }
+
+    /// Invokes the interpreter to perform inference, see [`Interpreter::invoke()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error with the interpreter if TensorFlow Lite C fails to invoke or the
+    /// invocation is cancelled.
+    pub fn invoke(self) -> std::result::Result<TypedInterpreter<'a, Invoked>, StateError<Self>> {
+        match self.interpreter.invoke() {
+            Ok(()) => Ok(self.transition()),
+            Err(error) => Err(StateError::new(error, self)),
+        }
+    }
+}
+
+impl TypedInterpreter<'_, Invoked> {
+    /// Returns the output [`Tensor`] at the given `index`, see [`Interpreter::output()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error if given index is not a valid output tensor index.
+    pub fn output(&self, index: usize) -> Result<Tensor> {
+        self.interpreter.output(index)
+    }
+}
+
+/// The error of a failed state transition of a [`TypedInterpreter`], which gives the
+/// interpreter back in its previous state.
+///
+/// It converts into an [`Error`], so `?` can be used in functions returning
+/// [`Result`][crate::Result].
+pub struct StateError<T> {
+    error: Error,
+    interpreter: Box<T>,
+}
+
+impl<T> StateError<T> {
+    fn new(error: Error, interpreter: T) -> Self {
+        StateError {
+            error,
+            interpreter: Box::new(interpreter),
+        }
+    }
+
+    /// Returns the error which failed the transition.
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+
+    /// Returns the interpreter in its state before the transition.
+    pub fn into_interpreter(self) -> T {
+        *self.interpreter
+    }
+}
+
+impl<T> Debug for StateError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StateError")
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl<T> Display for StateError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.error, f)
+    }
+}
+
+impl<T> std::error::Error for StateError<T> {}
+
+impl<T> From<StateError<T>> for Error {
+    fn from(error: StateError<T>) -> Error {
+        error.error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lifecycle::TypedInterpreter;
+    use crate::model::Model;
+    use crate::tensor::Shape;
+    use crate::ErrorKind;
+
+    const MODEL_PATH: &str = "tests/add.bin";
+
+    #[test]
+    fn test_typed_interpreter() {
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let interpreter = TypedInterpreter::new(&model, None)
+            .unwrap()
+            .allocate()
+            .unwrap();
+        let error = interpreter
+            .resize_input(1, Shape::new(vec![2, 8, 8, 3]))
+            .unwrap_err();
+        assert_eq!(error.error().kind(), ErrorKind::InvalidTensorIndex(1, 0));
+
+        let interpreter = error.into_interpreter();
+        let data = (0..8 * 8 * 3).map(|x| x as f32).collect::<Vec<f32>>();
+        interpreter.copy(&data, 0).unwrap();
+        let interpreter = interpreter.invoke().unwrap();
+        let expected = data.iter().map(|x| x * 3.0).collect::<Vec<f32>>();
+        assert_eq!(interpreter.output(0).unwrap().data::<f32>(), expected);
+
+        let interpreter = interpreter
+            .resize_input(0, Shape::new(vec![2, 8, 8, 3]))
+            .unwrap()
+            .allocate()
+            .unwrap();
+        assert_eq!(
+            interpreter.input(0).unwrap().shape().dimensions(),
+            &vec![2, 8, 8, 3]
+        );
+        assert_eq!(interpreter.into_inner().input_tensor_count(), 1);
+    }
+}
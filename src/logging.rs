@@ -0,0 +1,34 @@
+//! Routing of TensorFlow Lite interpreter errors into `tracing` error events with the `tracing`
+//! feature, and into error `log::Record`s with the `log` feature. If both features are enabled,
+//! errors are only routed into `tracing`, which can forward its events to `log` with its own
+//! `log` feature.
+//!
+//! Errors of an [`Interpreter`][crate::interpreter::Interpreter] are reported by TensorFlow Lite
+//! through the error reporter set with `TfLiteInterpreterOptionsSetErrorReporter`, and are routed
+//! automatically unless an error reporter is set with
+//! [`OptionsBuilder::error_reporter()`][crate::interpreter::OptionsBuilder::error_reporter].
+//!
+//! - Note: Other runtime logs, e.g. `INFO: Created TensorFlow Lite XNNPACK delegate for CPU.`,
+//! are written to stderr by TensorFlow Lite, and its C API has neither a hook for them nor a
+//! control of their minimum severity. They are not routed, so this module has no severity
+//! controls.
+use crate::interpreter::ErrorReporter;
+
+/// The target of events and records emitted for TensorFlow Lite errors.
+pub const LOG_TARGET: &str = "tflite";
+
+/// Emits a TensorFlow Lite error.
+///
+/// Errors are emitted as `tracing` events if the `tracing` feature is enabled, and as `log`
+/// records otherwise.
+fn emit_error(message: &str) {
+    #[cfg(feature = "tracing")]
+    tracing::error!(target: LOG_TARGET, "{}", message);
+    #[cfg(all(feature = "log", not(feature = "tracing")))]
+    log::error!(target: LOG_TARGET, "{}", message);
+}
+
+/// Returns the error reporter of interpreters without a user-supplied one.
+pub(crate) fn error_reporter() -> ErrorReporter {
+    std::sync::Arc::new(emit_error)
+}
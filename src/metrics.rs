@@ -0,0 +1,438 @@
+//! Inference metrics of [`Interpreter`][crate::interpreter::Interpreter]s, recorded into a
+//! [`MetricsSink`] such as the built-in [`PrometheusSink`].
+//!
+//! Metrics are enabled per interpreter with
+//! [`OptionsBuilder::metrics()`][crate::interpreter::OptionsBuilder::metrics] and labelled with
+//! the name of the model given in [`Metrics::new()`].
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::{ErrorKind, Result};
+
+/// The counter of [`Interpreter::invoke()`][crate::interpreter::Interpreter::invoke] calls.
+pub const INVOCATIONS: &str = "tflite_invocations_total";
+
+/// The counter of failed calls, labelled with the `operation` and the [`ErrorKind`].
+pub const ERRORS: &str = "tflite_errors_total";
+
+/// The histogram of [`Interpreter::invoke()`][crate::interpreter::Interpreter::invoke]
+/// latencies in seconds.
+pub const INVOKE_DURATION: &str = "tflite_invoke_duration_seconds";
+
+/// The histogram of
+/// [`Interpreter::allocate_tensors()`][crate::interpreter::Interpreter::allocate_tensors]
+/// latencies in seconds.
+pub const ALLOCATE_DURATION: &str = "tflite_allocate_duration_seconds";
+
+/// The counter of
+/// [`Interpreter::resize_input()`][crate::interpreter::Interpreter::resize_input] calls.
+pub const RESIZES: &str = "tflite_resizes_total";
+
+/// The label of the model name.
+pub const MODEL_LABEL: &str = "model";
+
+/// The label of the failed operation, e.g. `invoke`.
+pub const OPERATION_LABEL: &str = "operation";
+
+/// The label of the [`ErrorKind`] of a failed operation, e.g. `AllocateTensorsRequired`.
+pub const ERROR_KIND_LABEL: &str = "kind";
+
+/// The upper bounds of histogram buckets of [`PrometheusSink::new()`] in seconds.
+pub const DEFAULT_BUCKETS: [f64; 14] = [
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// A receiver of metrics of [`Interpreter`][crate::interpreter::Interpreter]s.
+///
+/// Labels are given as name-value pairs, and always start with [`MODEL_LABEL`].
+///
+/// - Note: Panics of a sink are caught and ignored, so they do not fail the recorded call.
+pub trait MetricsSink: Send + Sync {
+    /// Increments the counter with the given `name` and `labels` by one.
+    fn increment_counter(&self, name: &'static str, labels: &[(&'static str, &str)]);
+
+    /// Records the given `value` into the histogram with the given `name` and `labels`.
+    fn observe_histogram(&self, name: &'static str, labels: &[(&'static str, &str)], value: f64);
+}
+
+/// The metrics configuration of an [`Interpreter`][crate::interpreter::Interpreter].
+#[derive(Clone)]
+pub struct Metrics {
+    sink: Arc<dyn MetricsSink>,
+    model: String,
+}
+
+impl std::fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Metrics")
+            .field("model", &self.model)
+            .finish()
+    }
+}
+
+impl Metrics {
+    /// Creates a new instance recording into `sink` with the given `model` label.
+    pub fn new(sink: Arc<dyn MetricsSink>, model: &str) -> Metrics {
+        Metrics {
+            sink,
+            model: model.to_string(),
+        }
+    }
+
+    /// Returns the sink metrics are recorded into.
+    pub fn sink(&self) -> &Arc<dyn MetricsSink> {
+        &self.sink
+    }
+
+    /// Returns the model label of the metrics.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    pub(crate) fn record_invoke(&self, duration: Duration, result: &Result<()>) {
+        crate::catch_unwind((), || {
+            let labels = [(MODEL_LABEL, self.model.as_str())];
+            self.sink.increment_counter(INVOCATIONS, &labels);
+            self.sink
+                .observe_histogram(INVOKE_DURATION, &labels, duration.as_secs_f64());
+            self.record_error("invoke", result);
+        })
+    }
+
+    pub(crate) fn record_allocate(&self, duration: Duration, result: &Result<()>) {
+        crate::catch_unwind((), || {
+            let labels = [(MODEL_LABEL, self.model.as_str())];
+            self.sink
+                .observe_histogram(ALLOCATE_DURATION, &labels, duration.as_secs_f64());
+            self.record_error("allocate_tensors", result);
+        })
+    }
+
+    pub(crate) fn record_resize(&self, result: &Result<()>) {
+        crate::catch_unwind((), || {
+            let labels = [(MODEL_LABEL, self.model.as_str())];
+            self.sink.increment_counter(RESIZES, &labels);
+            self.record_error("resize_input", result);
+        })
+    }
+
+    fn record_error(&self, operation: &str, result: &Result<()>) {
+        if let Err(error) = result {
+            let kind = error_kind_label(error.kind());
+            self.sink.increment_counter(
+                ERRORS,
+                &[
+                    (MODEL_LABEL, self.model.as_str()),
+                    (OPERATION_LABEL, operation),
+                    (ERROR_KIND_LABEL, &kind),
+                ],
+            );
+        }
+    }
+}
+
+/// Returns the name of the variant of `kind`, without its values.
+fn error_kind_label(kind: ErrorKind) -> String {
+    let name = format!("{:?}", kind);
+    match name.find('(') {
+        Some(end) => name[..end].to_string(),
+        None => name,
+    }
+}
+
+/// The help text of the metrics recorded by [`Metrics`].
+fn help(name: &str) -> Option<&'static str> {
+    match name {
+        INVOCATIONS => Some("Number of interpreter invocations."),
+        ERRORS => Some("Number of failed interpreter operations."),
+        INVOKE_DURATION => Some("Latency of interpreter invocations in seconds."),
+        ALLOCATE_DURATION => Some("Latency of tensor allocations in seconds."),
+        RESIZES => Some("Number of input tensor resizes."),
+        _ => None,
+    }
+}
+
+type Labels = Vec<(&'static str, String)>;
+
+#[derive(Debug)]
+struct Histogram {
+    /// The number of values per bucket, the last one counting values above all bounds.
+    bucket_counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+#[derive(Debug, Default)]
+struct PrometheusState {
+    counters: BTreeMap<&'static str, BTreeMap<Labels, u64>>,
+    histograms: BTreeMap<&'static str, BTreeMap<Labels, Histogram>>,
+}
+
+/// A [`MetricsSink`] keeping metrics in memory and rendering them in the Prometheus text
+/// exposition format, e.g. to be served on a `/metrics` endpoint.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use tflitec::interpreter::{Interpreter, Options};
+/// use tflitec::metrics::{Metrics, PrometheusSink};
+/// use tflitec::model::Model;
+///
+/// let sink = Arc::new(PrometheusSink::new());
+/// let options = Options::builder()
+///     .metrics(Metrics::new(sink.clone(), "add"))
+///     .build()?;
+/// let model = Model::new("tests/add.bin")?;
+/// let interpreter = Interpreter::new(&model, Some(options))?;
+/// interpreter.allocate_tensors()?;
+/// interpreter.invoke()?;
+/// assert!(sink
+///     .render()
+///     .contains("tflite_invocations_total{model=\"add\"} 1\n"));
+/// # Ok::<(), tflitec::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct PrometheusSink {
+    buckets: Vec<f64>,
+    state: Mutex<PrometheusState>,
+}
+
+impl Default for PrometheusSink {
+    fn default() -> Self {
+        PrometheusSink::new()
+    }
+}
+
+impl PrometheusSink {
+    /// Creates a new instance with [`DEFAULT_BUCKETS`].
+    pub fn new() -> PrometheusSink {
+        PrometheusSink::with_buckets(&DEFAULT_BUCKETS)
+    }
+
+    /// Creates a new instance with the given upper bounds of histogram buckets.
+    pub fn with_buckets(buckets: &[f64]) -> PrometheusSink {
+        let mut buckets = buckets.to_vec();
+        buckets.sort_by(f64::total_cmp);
+        buckets.dedup();
+        PrometheusSink {
+            buckets,
+            state: Mutex::new(PrometheusState::default()),
+        }
+    }
+
+    /// Returns the value of the counter with the given `name` and `labels`, if recorded.
+    pub fn counter(&self, name: &str, labels: &[(&str, &str)]) -> Option<u64> {
+        let state = self.state.lock().unwrap();
+        state.counters.get(name).and_then(|series| {
+            series
+                .iter()
+                .find(|(series_labels, _)| labels_eq(series_labels, labels))
+                .map(|(_, value)| *value)
+        })
+    }
+
+    /// Removes all recorded metrics.
+    pub fn clear(&self) {
+        *self.state.lock().unwrap() = PrometheusState::default();
+    }
+
+    /// Renders all recorded metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let state = self.state.lock().unwrap();
+        let mut text = String::new();
+        for (name, series) in state.counters.iter() {
+            write_header(&mut text, name, "counter");
+            for (labels, value) in series.iter() {
+                writeln!(text, "{}{} {}", name, format_labels(labels, None), value).unwrap();
+            }
+        }
+        for (name, series) in state.histograms.iter() {
+            write_header(&mut text, name, "histogram");
+            for (labels, histogram) in series.iter() {
+                let mut cumulative_count = 0;
+                for (index, count) in histogram.bucket_counts.iter().enumerate() {
+                    cumulative_count += count;
+                    let bound = self
+                        .buckets
+                        .get(index)
+                        .map(|bound| bound.to_string())
+                        .unwrap_or_else(|| "+Inf".to_string());
+                    writeln!(
+                        text,
+                        "{}_bucket{} {}",
+                        name,
+                        format_labels(labels, Some(&bound)),
+                        cumulative_count
+                    )
+                    .unwrap();
+                }
+                let labels = format_labels(labels, None);
+                writeln!(text, "{}_sum{} {}", name, labels, histogram.sum).unwrap();
+                writeln!(text, "{}_count{} {}", name, labels, histogram.count).unwrap();
+            }
+        }
+        text
+    }
+}
+
+impl MetricsSink for PrometheusSink {
+    fn increment_counter(&self, name: &'static str, labels: &[(&'static str, &str)]) {
+        let mut state = self.state.lock().unwrap();
+        *state
+            .counters
+            .entry(name)
+            .or_default()
+            .entry(owned_labels(labels))
+            .or_default() += 1;
+    }
+
+    fn observe_histogram(&self, name: &'static str, labels: &[(&'static str, &str)], value: f64) {
+        let bucket_count = self.buckets.len() + 1;
+        let bucket = self
+            .buckets
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(self.buckets.len());
+        let mut state = self.state.lock().unwrap();
+        let histogram = state
+            .histograms
+            .entry(name)
+            .or_default()
+            .entry(owned_labels(labels))
+            .or_insert_with(|| Histogram {
+                bucket_counts: vec![0; bucket_count],
+                sum: 0.0,
+                count: 0,
+            });
+        histogram.bucket_counts[bucket] += 1;
+        histogram.sum += value;
+        histogram.count += 1;
+    }
+}
+
+fn owned_labels(labels: &[(&'static str, &str)]) -> Labels {
+    labels
+        .iter()
+        .map(|(name, value)| (*name, value.to_string()))
+        .collect()
+}
+
+fn labels_eq(labels: &[(&'static str, String)], other: &[(&str, &str)]) -> bool {
+    labels.len() == other.len()
+        && labels
+            .iter()
+            .zip(other.iter())
+            .all(|((name, value), (other_name, other_value))| {
+                name == other_name && value == other_value
+            })
+}
+
+fn write_header(text: &mut String, name: &str, metric_type: &str) {
+    if let Some(help) = help(name) {
+        writeln!(text, "# HELP {} {}", name, help).unwrap();
+    }
+    writeln!(text, "# TYPE {} {}", name, metric_type).unwrap();
+}
+
+/// Formats `labels` and the optional `le` label of a histogram bucket, e.g. `{model="a"}`.
+fn format_labels(labels: &[(&'static str, String)], le: Option<&str>) -> String {
+    let pairs = labels
+        .iter()
+        .map(|(name, value)| (*name, value.as_str()))
+        .chain(le.map(|le| ("le", le)))
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape_label_value(value)))
+        .collect::<Vec<_>>();
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::interpreter::{Interpreter, Options};
+    use crate::metrics::{
+        Metrics, MetricsSink, PrometheusSink, ERRORS, INVOCATIONS, MODEL_LABEL, RESIZES,
+    };
+    use crate::model::Model;
+    use crate::tensor;
+
+    const MODEL_PATH: &str = "tests/add.bin";
+
+    #[test]
+    fn test_prometheus_sink_render() {
+        let sink = PrometheusSink::with_buckets(&[1.0, 0.5]);
+        sink.increment_counter("requests_total", &[("model", "a\"b")]);
+        sink.increment_counter("requests_total", &[("model", "a\"b")]);
+        sink.observe_histogram("latency_seconds", &[("model", "a")], 0.25);
+        sink.observe_histogram("latency_seconds", &[("model", "a")], 0.75);
+        sink.observe_histogram("latency_seconds", &[("model", "a")], 2.0);
+        assert_eq!(
+            sink.render(),
+            "# TYPE requests_total counter\n\
+             requests_total{model=\"a\\\"b\"} 2\n\
+             # TYPE latency_seconds histogram\n\
+             latency_seconds_bucket{model=\"a\",le=\"0.5\"} 1\n\
+             latency_seconds_bucket{model=\"a\",le=\"1\"} 2\n\
+             latency_seconds_bucket{model=\"a\",le=\"+Inf\"} 3\n\
+             latency_seconds_sum{model=\"a\"} 3\n\
+             latency_seconds_count{model=\"a\"} 3\n"
+        );
+        sink.clear();
+        assert_eq!(sink.render(), "");
+    }
+
+    #[test]
+    fn test_interpreter_metrics() {
+        let sink = Arc::new(PrometheusSink::new());
+        let options = Options::builder()
+            .metrics(Metrics::new(sink.clone(), "add"))
+            .build()
+            .unwrap();
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let interpreter = Interpreter::new(&model, Some(options)).unwrap();
+        assert!(interpreter.invoke().is_err());
+        interpreter
+            .resize_input(0, tensor::Shape::new(vec![2, 8, 8, 3]))
+            .unwrap();
+        interpreter.allocate_tensors().unwrap();
+        interpreter.invoke().unwrap();
+
+        let model_labels = [(MODEL_LABEL, "add")];
+        assert_eq!(sink.counter(INVOCATIONS, &model_labels), Some(2));
+        assert_eq!(sink.counter(RESIZES, &model_labels), Some(1));
+        assert_eq!(
+            sink.counter(
+                ERRORS,
+                &[
+                    (MODEL_LABEL, "add"),
+                    ("operation", "invoke"),
+                    ("kind", "AllocateTensorsRequired")
+                ]
+            ),
+            Some(1)
+        );
+        let text = sink.render();
+        assert!(text.contains("tflite_invoke_duration_seconds_count{model=\"add\"} 2\n"));
+        assert!(text.contains("tflite_allocate_duration_seconds_count{model=\"add\"} 1\n"));
+    }
+}
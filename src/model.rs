@@ -10,9 +10,11 @@
 use crate::bindings::{
     TfLiteModel, TfLiteModelCreate, TfLiteModelCreateFromFile, TfLiteModelDelete,
 };
+use crate::validator::ModelValidator;
 use crate::{Error, ErrorKind, Result};
 use std::ffi::{c_void, CString};
 use std::fmt::{Debug, Formatter};
+use std::sync::Mutex;
 
 /// A TensorFlow Lite model used by the [`Interpreter`][crate::interpreter::Interpreter] to perform inference.
 pub struct Model<'a> {
@@ -25,6 +27,9 @@ pub struct Model<'a> {
     /// This reference is taken to guarantee that bytes
     /// must be immutable and outlive the model
     pub(crate) bytes: Option<&'a [u8]>,
+
+    /// The validators which allowed all operators of the model.
+    validated: Mutex<Vec<ModelValidator>>,
 }
 
 impl Debug for Model<'_> {
@@ -47,6 +52,10 @@ impl Model<'_> {
     ///
     /// Returns error if TensorFlow Lite C fails to read model from file.
     pub fn new<'a>(filepath: &str) -> Result<Model<'a>> {
+        #[cfg(feature = "tracing")]
+        let span =
+            tracing::info_span!("Model::new", path = filepath, model = tracing::field::Empty)
+                .entered();
         let model_ptr = unsafe {
             let path = CString::new(filepath).unwrap();
             TfLiteModelCreateFromFile(path.as_ptr())
@@ -54,9 +63,12 @@ impl Model<'_> {
         if model_ptr.is_null() {
             Err(Error::new(ErrorKind::FailedToLoadModel))
         } else {
+            #[cfg(feature = "tracing")]
+            span.record("model", tracing::field::debug(model_ptr));
             Ok(Model {
                 model_ptr,
                 bytes: None,
+                validated: Mutex::new(Vec::new()),
             })
         }
     }
@@ -71,16 +83,54 @@ impl Model<'_> {
     ///
     /// Returns error if TensorFlow Lite C fails to load model from the buffer.
     pub fn from_bytes(bytes: &[u8]) -> std::result::Result<Model, Error> {
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "Model::from_bytes",
+            size = bytes.len(),
+            model = tracing::field::Empty
+        )
+        .entered();
         let model_ptr = unsafe { TfLiteModelCreate(bytes.as_ptr() as *const c_void, bytes.len()) };
         if model_ptr.is_null() {
             Err(Error::new(ErrorKind::FailedToLoadModel))
         } else {
+            #[cfg(feature = "tracing")]
+            span.record("model", tracing::field::debug(model_ptr));
             Ok(Model {
                 model_ptr,
                 bytes: Some(bytes),
+                validated: Mutex::new(Vec::new()),
             })
         }
     }
+
+    /// Checks that all operators of the model are allowed by `validator`, which is only done
+    /// once for equal validators.
+    pub(crate) fn validate(&self, validator: &ModelValidator) -> Result<()> {
+        let mut validated = self.validated.lock().unwrap();
+        if !validated.contains(validator) {
+            validator.validate(self)?;
+            validated.push(validator.clone());
+        }
+        Ok(())
+    }
+}
+
+/// The source of [`Model`]s loaded by threads which own them.
+#[derive(Debug)]
+pub(crate) enum ModelSource {
+    File(String),
+    Bytes(Vec<u8>),
+}
+
+impl ModelSource {
+    /// Loads a new [`Model`] from the source.
+    pub(crate) fn load(&self) -> Result<Model<'_>> {
+        match self {
+            ModelSource::File(filepath) => Model::new(filepath),
+            ModelSource::Bytes(bytes) => Model::from_bytes(bytes),
+        }
+    }
 }
 
 impl Drop for Model<'_> {
@@ -0,0 +1,384 @@
+//! An [`InterpreterPool`] sharing one [`Model`] between threads.
+use std::fmt::{Debug, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Condvar, Mutex};
+
+use crate::interpreter::{Interpreter, Options};
+use crate::model::Model;
+use crate::tensor::Shape;
+use crate::worker::{self, InterpreterSetup};
+use crate::{Error, ErrorKind, Result};
+
+#[derive(Debug)]
+struct PoolState<'a> {
+    /// The interpreters which are not checked out.
+    idle: Vec<Interpreter<'a>>,
+
+    /// The number of created interpreters, including the ones being created.
+    size: usize,
+
+    /// The shapes of all inputs of the prepared interpreters, known once one is created.
+    input_shapes: Option<Vec<Shape>>,
+}
+
+/// A pool of [`Interpreter`]s of the same [`Model`], created with the same [`Options`] and
+/// prepared with the same input shapes.
+///
+/// [`Interpreter`]s are not thread-safe, so each thread checks one out of the pool with
+/// [`InterpreterPool::get()`] and returns it when the [`PooledInterpreter`] is dropped.
+///
+/// # Examples
+///
+/// ```
+/// use tflitec::model::Model;
+/// use tflitec::pool::InterpreterPool;
+/// use tflitec::tensor::Shape;
+///
+/// let model = Model::new("tests/add.bin")?;
+/// let pool = InterpreterPool::builder(&model)
+///     .size(1)
+///     .max_size(4)
+///     .input_shape(0, Shape::new(vec![2, 8, 8, 3]))
+///     .build()?;
+/// std::thread::scope(|scope| {
+///     for _ in 0..8 {
+///         scope.spawn(|| {
+///             let interpreter = pool.get().unwrap();
+///             interpreter.copy(&[1.0f32; 2 * 8 * 8 * 3], 0).unwrap();
+///             interpreter.invoke().unwrap();
+///             assert_eq!(interpreter.output(0).unwrap().data::<f32>()[0], 3.0);
+///         });
+///     }
+/// });
+/// assert!(pool.size() <= 4);
+/// # Ok::<(), tflitec::Error>(())
+/// ```
+pub struct InterpreterPool<'a> {
+    model: &'a Model<'a>,
+    setup: InterpreterSetup,
+    max_size: usize,
+    state: Mutex<PoolState<'a>>,
+    /// Notified when an interpreter is returned or fails to be created.
+    available: Condvar,
+}
+
+impl Debug for InterpreterPool<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InterpreterPool")
+            .field("options", &self.setup.options)
+            .field("input_shapes", &self.setup.input_shapes)
+            .field("max_size", &self.max_size)
+            .field("size", &self.size())
+            .finish()
+    }
+}
+
+impl<'a> InterpreterPool<'a> {
+    /// Creates a new [`InterpreterPoolBuilder`] of pools of the given `model`.
+    pub fn builder(model: &'a Model<'a>) -> InterpreterPoolBuilder<'a> {
+        InterpreterPoolBuilder {
+            model,
+            setup: InterpreterSetup::default(),
+            size: 1,
+            max_size: None,
+        }
+    }
+
+    /// Returns the number of created interpreters, checked out or not.
+    pub fn size(&self) -> usize {
+        self.state.lock().unwrap().size
+    }
+
+    /// Returns the number of interpreters which are not checked out.
+    pub fn idle_count(&self) -> usize {
+        self.state.lock().unwrap().idle.len()
+    }
+
+    /// Returns the maximum number of interpreters of the pool.
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// Checks out an interpreter, waiting for one to be returned if all
+    /// [`InterpreterPool::max_size()`] interpreters are checked out.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if a new interpreter cannot be created or prepared.
+    pub fn get(&self) -> Result<PooledInterpreter<'_, 'a>> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(interpreter) = state.idle.pop() {
+                return Ok(self.checkout(interpreter));
+            }
+            if state.size < self.max_size {
+                state.size += 1;
+                drop(state);
+                return self.grow();
+            }
+            state = self.available.wait(state).unwrap();
+        }
+    }
+
+    /// Checks out an interpreter if one is idle or can be created, without waiting.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if a new interpreter cannot be created or prepared.
+    pub fn try_get(&self) -> Result<Option<PooledInterpreter<'_, 'a>>> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(interpreter) = state.idle.pop() {
+            return Ok(Some(self.checkout(interpreter)));
+        }
+        if state.size < self.max_size {
+            state.size += 1;
+            drop(state);
+            return self.grow().map(Some);
+        }
+        Ok(None)
+    }
+
+    /// Creates a checked out interpreter whose slot is already counted in the size.
+    fn grow(&self) -> Result<PooledInterpreter<'_, 'a>> {
+        match self.create_interpreter() {
+            Ok(interpreter) => Ok(self.checkout(interpreter)),
+            Err(error) => {
+                self.state.lock().unwrap().size -= 1;
+                self.available.notify_one();
+                Err(error)
+            }
+        }
+    }
+
+    /// Creates an interpreter and applies the input shapes of the pool to it.
+    fn create_interpreter(&self) -> Result<Interpreter<'a>> {
+        let interpreter = self.setup.create(self.model)?;
+        let input_shapes = worker::input_shapes(&interpreter)?;
+        self.state
+            .lock()
+            .unwrap()
+            .input_shapes
+            .get_or_insert(input_shapes);
+        Ok(interpreter)
+    }
+
+    /// Resizes the inputs of a returned interpreter back to the shapes of the pool if they
+    /// were resized while it was checked out.
+    fn restore(&self, interpreter: &Interpreter<'a>) -> Result<()> {
+        let input_shapes = worker::input_shapes(interpreter)?;
+        let pool_input_shapes = match self.state.lock().unwrap().input_shapes.as_ref() {
+            Some(pool_input_shapes) if *pool_input_shapes != input_shapes => {
+                pool_input_shapes.clone()
+            }
+            _ => return Ok(()),
+        };
+        worker::prepare(interpreter, pool_input_shapes.iter().enumerate())
+    }
+
+    fn checkout(&self, interpreter: Interpreter<'a>) -> PooledInterpreter<'_, 'a> {
+        PooledInterpreter {
+            pool: self,
+            interpreter: Some(interpreter),
+        }
+    }
+}
+
+/// The builder of [`InterpreterPool`]s, see [`InterpreterPool::builder()`].
+#[derive(Debug)]
+pub struct InterpreterPoolBuilder<'a> {
+    model: &'a Model<'a>,
+    setup: InterpreterSetup,
+    size: usize,
+    max_size: Option<usize>,
+}
+
+impl<'a> InterpreterPoolBuilder<'a> {
+    /// Sets the [`Options`] of all interpreters of the pool.
+    pub fn options(mut self, options: Options) -> Self {
+        self.setup.options = Some(options);
+        self
+    }
+
+    /// Sets the number of interpreters created by [`InterpreterPoolBuilder::build()`].
+    ///
+    /// Defaults to 1.
+    pub fn size(mut self, size: usize) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets the maximum number of interpreters, created lazily when all others are checked
+    /// out.
+    ///
+    /// Defaults to the initial [size][InterpreterPoolBuilder::size], i.e. the pool does not grow.
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Resizes the input tensor at the given `index` of all interpreters to `shape` before
+    /// their tensors are allocated.
+    pub fn input_shape(mut self, index: usize, shape: Shape) -> Self {
+        self.setup.input_shapes.push((index, shape));
+        self
+    }
+
+    /// Returns the built [`InterpreterPool`] with its initial interpreters.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the maximum size is 0 or less than the initial size, or an interpreter
+    /// cannot be created or prepared.
+    pub fn build(self) -> Result<InterpreterPool<'a>> {
+        let max_size = self.max_size.unwrap_or(self.size);
+        if max_size == 0 || self.size > max_size {
+            return Err(Error::new(ErrorKind::InvalidPoolSize(self.size, max_size)));
+        }
+        let pool = InterpreterPool {
+            model: self.model,
+            setup: self.setup,
+            max_size,
+            state: Mutex::new(PoolState {
+                idle: Vec::with_capacity(max_size),
+                size: 0,
+                input_shapes: None,
+            }),
+            available: Condvar::new(),
+        };
+        let interpreters = (0..self.size)
+            .map(|_| pool.create_interpreter())
+            .collect::<Result<Vec<_>>>()?;
+        {
+            let mut state = pool.state.lock().unwrap();
+            state.size = interpreters.len();
+            state.idle = interpreters;
+        }
+        Ok(pool)
+    }
+}
+
+/// An [`Interpreter`] checked out of an [`InterpreterPool`], returned to it when dropped.
+///
+/// If its inputs were resized, they are resized back to the shapes of the pool when it is
+/// returned, and it is dropped instead if that fails.
+#[derive(Debug)]
+pub struct PooledInterpreter<'p, 'a> {
+    pool: &'p InterpreterPool<'a>,
+    interpreter: Option<Interpreter<'a>>,
+}
+
+impl<'a> Deref for PooledInterpreter<'_, 'a> {
+    type Target = Interpreter<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        self.interpreter.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledInterpreter<'_, '_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.interpreter.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledInterpreter<'_, '_> {
+    fn drop(&mut self) {
+        if let Some(interpreter) = self.interpreter.take() {
+            let is_restored = self.pool.restore(&interpreter).is_ok();
+            let mut state = self.pool.state.lock().unwrap();
+            if is_restored {
+                state.idle.push(interpreter);
+            } else {
+                state.size -= 1;
+            }
+            drop(state);
+            self.pool.available.notify_one();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::Model;
+    use crate::pool::InterpreterPool;
+    use crate::tensor::Shape;
+    use crate::ErrorKind;
+
+    const MODEL_PATH: &str = "tests/add.bin";
+
+    #[test]
+    fn test_interpreter_pool() {
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let error = InterpreterPool::builder(&model)
+            .size(2)
+            .max_size(1)
+            .build()
+            .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidPoolSize(2, 1));
+
+        let pool = InterpreterPool::builder(&model)
+            .size(0)
+            .max_size(2)
+            .input_shape(0, Shape::new(vec![2, 8, 8, 3]))
+            .build()
+            .unwrap();
+        assert_eq!(pool.size(), 0);
+        {
+            let first = pool.get().unwrap();
+            let second = pool.try_get().unwrap().unwrap();
+            assert!(pool.try_get().unwrap().is_none());
+            assert_eq!(
+                first.input(0).unwrap().shape().dimensions(),
+                &vec![2, 8, 8, 3]
+            );
+            assert_eq!(
+                second.input(0).unwrap().shape().dimensions(),
+                &vec![2, 8, 8, 3]
+            );
+            assert_eq!(pool.idle_count(), 0);
+        }
+        assert_eq!(pool.size(), 2);
+        assert_eq!(pool.idle_count(), 2);
+
+        // Inputs resized by a borrower are resized back when the interpreter is returned.
+        {
+            let first = pool.get().unwrap();
+            let second = pool.get().unwrap();
+            for interpreter in [&first, &second].iter() {
+                interpreter
+                    .resize_input(0, Shape::new(vec![1, 8, 8, 3]))
+                    .unwrap();
+                interpreter.allocate_tensors().unwrap();
+            }
+        }
+        assert_eq!(pool.size(), 2);
+        assert_eq!(pool.idle_count(), 2);
+        {
+            let first = pool.get().unwrap();
+            let second = pool.get().unwrap();
+            for interpreter in [&first, &second].iter() {
+                assert_eq!(
+                    interpreter.input(0).unwrap().shape().dimensions(),
+                    &vec![2, 8, 8, 3]
+                );
+            }
+        }
+
+        let data = (0..2 * 8 * 8 * 3).map(|x| x as f32).collect::<Vec<f32>>();
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..8 {
+                        let interpreter = pool.get().unwrap();
+                        interpreter.copy(&data, 0).unwrap();
+                        interpreter.invoke().unwrap();
+                        let output = interpreter.output(0).unwrap();
+                        let expected = data.iter().map(|x| x * 3.0).collect::<Vec<f32>>();
+                        assert_eq!(output.data::<f32>(), expected.as_slice());
+                    }
+                });
+            }
+        });
+        assert_eq!(pool.size(), 2);
+    }
+}
@@ -0,0 +1,637 @@
+//! Hooks into the telemetry profiler of TensorFlow Lite.
+//!
+//! [`Profiler`] records the operators run by an
+//! [`Interpreter`][crate::interpreter::Interpreter] and aggregates them into a [`ProfileReport`].
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::fmt::{Display, Formatter};
+use std::os::raw::c_char;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::bindings::*;
+use crate::delegate::KernelDelegates;
+
+/// A receiver of telemetry events of the [`Interpreter`][crate::interpreter::Interpreter].
+///
+/// Attach it with
+/// [`OptionsBuilder::telemetry_profiler()`][crate::interpreter::OptionsBuilder::telemetry_profiler].
+/// All methods have empty default implementations, so implementors only override the events
+/// they are interested in.
+///
+/// - Note: Events are reported synchronously from the thread running the interpreter,
+/// implementations should be cheap. Panics of the profiler are caught and ignored, since they
+/// cannot unwind through TensorFlow Lite C.
+pub trait TelemetryProfiler: Send + Sync {
+    /// Reports a telemetry event with the given `status`.
+    fn report_event(&self, _event_name: &str, _status: u64) {}
+
+    /// Reports a telemetry event of the operator at `op_index` of the subgraph at
+    /// `subgraph_index`.
+    fn report_op_event(
+        &self,
+        _event_name: &str,
+        _op_index: i64,
+        _subgraph_index: i64,
+        _status: u64,
+    ) {
+    }
+
+    /// Reports the beginning of the invocation of an operator and returns a handle
+    /// which will be passed to [`TelemetryProfiler::end_op_invoke()`].
+    fn begin_op_invoke(&self, _op_name: &str, _op_index: i64, _subgraph_index: i64) -> u32 {
+        0
+    }
+
+    /// Reports the beginning of the invocation of a kernel of the delegate with the given name,
+    /// see [`DelegateReport::name`][crate::delegate::DelegateReport::name], and returns a handle
+    /// which will be passed to [`TelemetryProfiler::end_op_invoke()`].
+    ///
+    /// It calls [`TelemetryProfiler::begin_op_invoke()`] by default.
+    fn begin_delegate_op_invoke(
+        &self,
+        op_name: &str,
+        _delegate: &str,
+        op_index: i64,
+        subgraph_index: i64,
+    ) -> u32 {
+        self.begin_op_invoke(op_name, op_index, subgraph_index)
+    }
+
+    /// Reports the end of the operator invocation started with the given `event_handle`.
+    fn end_op_invoke(&self, _event_handle: u32) {}
+
+    /// Reports a complete operator invocation which took `elapsed_us` microseconds.
+    fn report_op_invoke(
+        &self,
+        _op_name: &str,
+        _elapsed_us: u64,
+        _op_index: i64,
+        _subgraph_index: i64,
+    ) {
+    }
+
+    /// Reports a complete invocation of a kernel of the delegate with the given name which took
+    /// `elapsed_us` microseconds.
+    ///
+    /// It calls [`TelemetryProfiler::report_op_invoke()`] by default.
+    fn report_delegate_op_invoke(
+        &self,
+        op_name: &str,
+        _delegate: &str,
+        elapsed_us: u64,
+        op_index: i64,
+        subgraph_index: i64,
+    ) {
+        self.report_op_invoke(op_name, elapsed_us, op_index, subgraph_index)
+    }
+}
+
+/// A single invocation of an operator recorded by [`Profiler`].
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct OpEvent {
+    /// The name of the operator, e.g. `ADD`, or the name of the delegate kernel.
+    pub op_name: String,
+
+    /// The name of the delegate if the node is a delegate kernel, see
+    /// [`DelegateReport::name`][crate::delegate::DelegateReport::name].
+    pub delegate: Option<String>,
+
+    /// The index of the node in its subgraph.
+    pub op_index: i64,
+
+    /// The index of the subgraph of the node.
+    pub subgraph_index: i64,
+
+    /// The time the invocation started.
+    pub start: Instant,
+
+    /// The duration of the invocation.
+    pub duration: Duration,
+}
+
+impl OpEvent {
+    /// Returns whether the node is a delegate kernel.
+    pub fn is_delegate_kernel(&self) -> bool {
+        self.delegate.is_some()
+    }
+}
+
+impl OpEvent {
+    /// Creates an event of a complete invocation which took `elapsed_us` microseconds and
+    /// ended now.
+    pub(crate) fn complete(
+        op_name: &str,
+        delegate: Option<&str>,
+        elapsed_us: u64,
+        op_index: i64,
+        subgraph_index: i64,
+    ) -> OpEvent {
+        let duration = Duration::from_micros(elapsed_us);
+        let now = Instant::now();
+        OpEvent {
+            op_name: op_name.to_owned(),
+            delegate: delegate.map(str::to_owned),
+            op_index,
+            subgraph_index,
+            start: now.checked_sub(duration).unwrap_or(now),
+            duration,
+        }
+    }
+}
+
+/// The operator invocations which have not ended yet, keyed by the handles passed to
+/// [`TelemetryProfiler::end_op_invoke()`].
+#[derive(Debug, Default)]
+pub(crate) struct OpEventTracker {
+    /// The handle of the next invocation.
+    next_handle: u32,
+
+    /// The invocations which have not ended yet, whose durations are not set.
+    open_events: HashMap<u32, OpEvent>,
+}
+
+impl OpEventTracker {
+    /// Starts an invocation and returns its handle.
+    pub(crate) fn begin(
+        &mut self,
+        op_name: &str,
+        delegate: Option<&str>,
+        op_index: i64,
+        subgraph_index: i64,
+    ) -> u32 {
+        let handle = self.next_handle;
+        self.next_handle = self.next_handle.wrapping_add(1);
+        self.open_events.insert(
+            handle,
+            OpEvent {
+                op_name: op_name.to_owned(),
+                delegate: delegate.map(str::to_owned),
+                op_index,
+                subgraph_index,
+                start: Instant::now(),
+                duration: Duration::ZERO,
+            },
+        );
+        handle
+    }
+
+    /// Ends the invocation with the given handle, or returns [`None`] if it is unknown.
+    pub(crate) fn end(&mut self, handle: u32) -> Option<OpEvent> {
+        let end = Instant::now();
+        self.open_events.remove(&handle).map(|mut event| {
+            event.duration = end.duration_since(event.start);
+            event
+        })
+    }
+
+    /// Drops all invocations which have not ended yet.
+    pub(crate) fn clear(&mut self) {
+        self.open_events.clear();
+    }
+}
+
+#[derive(Debug, Default)]
+struct ProfilerState {
+    /// The invocations which have not ended yet.
+    open_events: OpEventTracker,
+
+    /// The recorded invocations in the order they ended.
+    events: Vec<OpEvent>,
+}
+
+/// A [`TelemetryProfiler`] recording every operator invocation of the interpreters it is
+/// attached to.
+///
+/// Recording is opt-in: attach it with
+/// [`OptionsBuilder::telemetry_profiler()`][crate::interpreter::OptionsBuilder::telemetry_profiler].
+/// A profiler can be shared by several interpreters.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use tflitec::interpreter::{Interpreter, Options};
+/// use tflitec::model::Model;
+/// use tflitec::profiler::Profiler;
+/// let profiler = Arc::new(Profiler::new());
+/// let options = Options::builder()
+///     .telemetry_profiler(profiler.clone())
+///     .build()?;
+/// let model = Model::new("tests/add.bin")?;
+/// let interpreter = Interpreter::new(&model, Some(options))?;
+/// interpreter.allocate_tensors()?;
+/// interpreter.invoke()?;
+/// println!("{}", profiler.report());
+/// # Ok::<(), tflitec::Error>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct Profiler {
+    state: Mutex<ProfilerState>,
+}
+
+impl Profiler {
+    /// Creates a new profiler without any recorded event.
+    pub fn new() -> Profiler {
+        Profiler::default()
+    }
+
+    /// Returns the recorded operator invocations in the order they ended.
+    pub fn events(&self) -> Vec<OpEvent> {
+        self.state.lock().unwrap().events.clone()
+    }
+
+    /// Removes all recorded events, including the invocations which have not ended yet.
+    pub fn reset(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.open_events.clear();
+        state.events.clear();
+    }
+
+    /// Returns the recorded invocations aggregated per operator.
+    pub fn report(&self) -> ProfileReport {
+        ProfileReport::new(&self.state.lock().unwrap().events)
+    }
+
+    fn record(&self, event: OpEvent) {
+        self.state.lock().unwrap().events.push(event);
+    }
+}
+
+impl TelemetryProfiler for Profiler {
+    fn begin_op_invoke(&self, op_name: &str, op_index: i64, subgraph_index: i64) -> u32 {
+        let mut state = self.state.lock().unwrap();
+        state
+            .open_events
+            .begin(op_name, None, op_index, subgraph_index)
+    }
+
+    fn begin_delegate_op_invoke(
+        &self,
+        op_name: &str,
+        delegate: &str,
+        op_index: i64,
+        subgraph_index: i64,
+    ) -> u32 {
+        let mut state = self.state.lock().unwrap();
+        state
+            .open_events
+            .begin(op_name, Some(delegate), op_index, subgraph_index)
+    }
+
+    fn end_op_invoke(&self, event_handle: u32) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(event) = state.open_events.end(event_handle) {
+            state.events.push(event);
+        }
+    }
+
+    fn report_op_invoke(&self, op_name: &str, elapsed_us: u64, op_index: i64, subgraph_index: i64) {
+        self.record(OpEvent::complete(
+            op_name,
+            None,
+            elapsed_us,
+            op_index,
+            subgraph_index,
+        ))
+    }
+
+    fn report_delegate_op_invoke(
+        &self,
+        op_name: &str,
+        delegate: &str,
+        elapsed_us: u64,
+        op_index: i64,
+        subgraph_index: i64,
+    ) {
+        self.record(OpEvent::complete(
+            op_name,
+            Some(delegate),
+            elapsed_us,
+            op_index,
+            subgraph_index,
+        ))
+    }
+}
+
+/// The statistics of an operator in a [`ProfileReport`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpStatistics {
+    /// The name of the operator, see [`OpEvent::op_name`].
+    pub op_name: String,
+
+    /// The name of the delegate, see [`OpEvent::delegate`].
+    pub delegate: Option<String>,
+
+    /// The number of invocations.
+    pub count: usize,
+
+    /// The total duration of the invocations.
+    pub total: Duration,
+
+    /// The mean duration of an invocation.
+    pub mean: Duration,
+
+    /// The percentage of `total` in the total duration of all operators.
+    pub percentage: f64,
+}
+
+/// Operator invocations aggregated per operator, see [`Profiler::report()`].
+///
+/// It is printed as a table with [`Display`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProfileReport {
+    /// The statistics of operators in descending order of their total duration.
+    operators: Vec<OpStatistics>,
+
+    /// The total duration of all operators.
+    total: Duration,
+}
+
+impl ProfileReport {
+    /// Aggregates the given events per operator.
+    pub fn new(events: &[OpEvent]) -> ProfileReport {
+        let mut operators: Vec<OpStatistics> = Vec::new();
+        let mut indices: HashMap<&str, usize> = HashMap::new();
+        for event in events {
+            let index = *indices.entry(event.op_name.as_str()).or_insert_with(|| {
+                operators.push(OpStatistics {
+                    op_name: event.op_name.clone(),
+                    delegate: event.delegate.clone(),
+                    count: 0,
+                    total: Duration::ZERO,
+                    mean: Duration::ZERO,
+                    percentage: 0.0,
+                });
+                operators.len() - 1
+            });
+            operators[index].count += 1;
+            operators[index].total += event.duration;
+        }
+        let total: Duration = operators.iter().map(|operator| operator.total).sum();
+        for operator in operators.iter_mut() {
+            operator.mean = operator.total / operator.count as u32;
+            if !total.is_zero() {
+                operator.percentage = 100.0 * operator.total.as_secs_f64() / total.as_secs_f64();
+            }
+        }
+        operators.sort_by_key(|operator| std::cmp::Reverse(operator.total));
+        ProfileReport { operators, total }
+    }
+
+    /// Returns the statistics of operators in descending order of their total duration.
+    pub fn operators(&self) -> &[OpStatistics] {
+        &self.operators
+    }
+
+    /// Returns the total duration of all operators.
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+}
+
+impl Display for ProfileReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<32} {:<24} {:>8} {:>12} {:>12} {:>8}",
+            "operator", "delegate", "count", "total (ms)", "mean (us)", "%"
+        )?;
+        for operator in self.operators.iter() {
+            writeln!(
+                f,
+                "{:<32} {:<24} {:>8} {:>12.3} {:>12.1} {:>8.2}",
+                operator.op_name,
+                operator.delegate.as_deref().unwrap_or("-"),
+                operator.count,
+                operator.total.as_secs_f64() * 1e3,
+                operator.mean.as_secs_f64() * 1e6,
+                operator.percentage
+            )?;
+        }
+        write!(f, "total: {:.3} ms", self.total.as_secs_f64() * 1e3)
+    }
+}
+
+/// A [`TelemetryProfiler`] attached to an interpreter, along with the delegates of its kernels.
+pub(crate) struct TelemetryContext {
+    profiler: Arc<dyn TelemetryProfiler>,
+    kernel_delegates: KernelDelegates,
+}
+
+impl TelemetryContext {
+    pub(crate) fn new(
+        profiler: Arc<dyn TelemetryProfiler>,
+        kernel_delegates: KernelDelegates,
+    ) -> TelemetryContext {
+        TelemetryContext {
+            profiler,
+            kernel_delegates,
+        }
+    }
+}
+
+/// Creates a [`TfLiteTelemetryProfilerStruct`] which forwards events to the given `context`.
+///
+/// The returned struct borrows `context`, so `context` must outlive it.
+pub(crate) fn telemetry_profiler_struct(
+    context: &TelemetryContext,
+) -> TfLiteTelemetryProfilerStruct {
+    TfLiteTelemetryProfilerStruct {
+        data: context as *const TelemetryContext as *mut std::ffi::c_void,
+        ReportTelemetryEvent: Some(report_telemetry_event),
+        ReportTelemetryOpEvent: Some(report_telemetry_op_event),
+        ReportSettings: Some(report_settings),
+        ReportBeginOpInvokeEvent: Some(report_begin_op_invoke_event),
+        ReportEndOpInvokeEvent: Some(report_end_op_invoke_event),
+        ReportOpInvokeEvent: Some(report_op_invoke_event),
+    }
+}
+
+unsafe fn context_from<'a>(profiler: *mut TfLiteTelemetryProfilerStruct) -> &'a TelemetryContext {
+    &*((*profiler).data as *const TelemetryContext)
+}
+
+unsafe fn str_from<'a>(name: *const c_char) -> &'a str {
+    if name.is_null() {
+        ""
+    } else {
+        CStr::from_ptr(name).to_str().unwrap_or("")
+    }
+}
+
+unsafe extern "C" fn report_telemetry_event(
+    profiler: *mut TfLiteTelemetryProfilerStruct,
+    event_name: *const c_char,
+    status: u64,
+) {
+    crate::catch_unwind((), || {
+        context_from(profiler)
+            .profiler
+            .report_event(str_from(event_name), status)
+    })
+}
+
+unsafe extern "C" fn report_telemetry_op_event(
+    profiler: *mut TfLiteTelemetryProfilerStruct,
+    event_name: *const c_char,
+    op_idx: i64,
+    subgraph_idx: i64,
+    status: u64,
+) {
+    crate::catch_unwind((), || {
+        context_from(profiler).profiler.report_op_event(
+            str_from(event_name),
+            op_idx,
+            subgraph_idx,
+            status,
+        )
+    })
+}
+
+unsafe extern "C" fn report_settings(
+    _profiler: *mut TfLiteTelemetryProfilerStruct,
+    _setting_name: *const c_char,
+    _settings: *const TfLiteTelemetrySettings,
+) {
+}
+
+unsafe extern "C" fn report_begin_op_invoke_event(
+    profiler: *mut TfLiteTelemetryProfilerStruct,
+    op_name: *const c_char,
+    op_idx: i64,
+    subgraph_idx: i64,
+) -> u32 {
+    crate::catch_unwind(0, || {
+        let context = context_from(profiler);
+        let op_name = str_from(op_name);
+        match context.kernel_delegates.delegate(op_name) {
+            Some(delegate) => {
+                context
+                    .profiler
+                    .begin_delegate_op_invoke(op_name, &delegate, op_idx, subgraph_idx)
+            }
+            None => context
+                .profiler
+                .begin_op_invoke(op_name, op_idx, subgraph_idx),
+        }
+    })
+}
+
+unsafe extern "C" fn report_end_op_invoke_event(
+    profiler: *mut TfLiteTelemetryProfilerStruct,
+    event_handle: u32,
+) {
+    crate::catch_unwind((), || {
+        context_from(profiler).profiler.end_op_invoke(event_handle)
+    })
+}
+
+unsafe extern "C" fn report_op_invoke_event(
+    profiler: *mut TfLiteTelemetryProfilerStruct,
+    op_name: *const c_char,
+    elapsed_time: u64,
+    op_idx: i64,
+    subgraph_idx: i64,
+) {
+    crate::catch_unwind((), || {
+        let context = context_from(profiler);
+        let op_name = str_from(op_name);
+        match context.kernel_delegates.delegate(op_name) {
+            Some(delegate) => context.profiler.report_delegate_op_invoke(
+                op_name,
+                &delegate,
+                elapsed_time,
+                op_idx,
+                subgraph_idx,
+            ),
+            None => context
+                .profiler
+                .report_op_invoke(op_name, elapsed_time, op_idx, subgraph_idx),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::delegate::{ArithmeticDelegate, OpaqueDelegateFactory};
+    use crate::interpreter::{Interpreter, Options};
+    use crate::model::Model;
+    use crate::profiler::{OpEvent, ProfileReport, Profiler, TelemetryProfiler};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    const MODEL_PATH: &str = "tests/add.bin";
+
+    #[test]
+    fn test_profile_report() {
+        let event = |op_name: &str, duration_us: u64| OpEvent {
+            op_name: op_name.to_string(),
+            delegate: None,
+            op_index: 0,
+            subgraph_index: 0,
+            start: Instant::now(),
+            duration: Duration::from_micros(duration_us),
+        };
+        let report = ProfileReport::new(&[event("ADD", 10), event("MUL", 40), event("ADD", 30)]);
+        assert_eq!(report.total(), Duration::from_micros(80));
+        let operators = report.operators();
+        assert_eq!(operators.len(), 2);
+        assert_eq!(operators[0].op_name, "ADD");
+        assert_eq!(operators[0].count, 2);
+        assert_eq!(operators[0].mean, Duration::from_micros(20));
+        assert!((operators[0].percentage - 50.0).abs() < 1e-9);
+        assert!(report.to_string().contains("MUL"));
+        assert!(!event("ADD", 10).is_delegate_kernel());
+    }
+
+    #[test]
+    fn test_profiler() {
+        let profiler = Arc::new(Profiler::new());
+        let options = Options::builder()
+            .telemetry_profiler(profiler.clone())
+            .build()
+            .expect("Cannot build options");
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let interpreter =
+            Interpreter::new(&model, Some(options)).expect("Cannot create interpreter!");
+        interpreter
+            .allocate_tensors()
+            .expect("Cannot allocate tensors");
+        assert!(interpreter.invoke().is_ok());
+        let events = profiler.events();
+        assert!(!events.is_empty());
+        assert!(events.iter().all(|event| event.op_name == "ADD"));
+        assert!(events.iter().all(|event| !event.is_delegate_kernel()));
+        let report = profiler.report();
+        assert_eq!(report.operators()[0].count, events.len());
+
+        let handle = profiler.begin_op_invoke("ADD", 0, 0);
+        profiler.reset();
+        profiler.end_op_invoke(handle);
+        assert!(profiler.events().is_empty());
+    }
+
+    #[test]
+    fn test_profiler_delegate_kernels() {
+        let profiler = Arc::new(Profiler::new());
+        let options = Options::builder()
+            .add_delegate(Arc::new(OpaqueDelegateFactory::new(ArithmeticDelegate)))
+            .telemetry_profiler(profiler.clone())
+            .build()
+            .expect("Cannot build options");
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let interpreter =
+            Interpreter::new(&model, Some(options)).expect("Cannot create interpreter!");
+        interpreter
+            .allocate_tensors()
+            .expect("Cannot allocate tensors");
+        assert!(interpreter.invoke().is_ok());
+        let events = profiler.events();
+        assert!(!events.is_empty());
+        for event in events.iter() {
+            assert_eq!(event.op_name, "ArithmeticDelegate");
+            assert_eq!(event.delegate.as_deref(), Some("ArithmeticDelegate"));
+        }
+    }
+}
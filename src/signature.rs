@@ -0,0 +1,343 @@
+//! Access to the signatures of a model, the entry points of its subgraphs.
+//!
+//! [`Interpreter::input()`][crate::interpreter::Interpreter::input] and the other methods of
+//! an [`Interpreter`][crate::interpreter::Interpreter] only expose the primary subgraph of a
+//! model. Models with multiple entry points export one signature for each of them, and a
+//! [`SignatureRunner`] resizes, allocates, and invokes the subgraph of one signature,
+//! identifying its inputs and outputs by their signature names.
+//!
+//! - Note: TensorFlow Lite C exposes subgraphs only through signatures. Subgraphs which are
+//! only called by control flow operators (e.g. the bodies of `WHILE` or the branches of
+//! `IF`) cannot be listed or invoked individually, and only the tensors of the primary
+//! subgraph are exposed by [`Interpreter::tensors()`][crate::interpreter::Interpreter::tensors].
+//! Operators of all subgraphs are reported with their subgraph index to a
+//! [`TelemetryProfiler`][crate::profiler::TelemetryProfiler].
+//!
+//! # Examples
+//!
+//! ```
+//! use tflitec::interpreter::Interpreter;
+//! use tflitec::model::Model;
+//!
+//! let model = Model::new("tests/signature.bin")?;
+//! let interpreter = Interpreter::new(&model, None)?;
+//! for key in interpreter.signature_keys() {
+//!     let runner = interpreter.signature_runner(&key)?;
+//!     println!("{}: {:?} -> {:?}", key, runner.input_names(), runner.output_names());
+//! }
+//! # Ok::<(), tflitec::Error>(())
+//! ```
+use std::ffi::{c_void, CStr, CString};
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::os::raw::c_int;
+
+use crate::bindings::*;
+use crate::tensor::{Shape, Tensor};
+use crate::{Error, ErrorKind, Result};
+
+/// A runner of the subgraph of one signature of a model, see
+/// [`Interpreter::signature_runner()`][crate::interpreter::Interpreter::signature_runner].
+///
+/// The inputs and outputs of the subgraph are identified by their names in the signature,
+/// which may differ from the names of their tensors.
+pub struct SignatureRunner<'i> {
+    /// The key of the signature.
+    key: String,
+
+    /// The underlying [`TfLiteSignatureRunner`] C pointer.
+    runner_ptr: *mut TfLiteSignatureRunner,
+
+    // To borrow the interpreter owning the subgraph
+    phantom: PhantomData<&'i TfLiteInterpreter>,
+}
+
+impl Debug for SignatureRunner<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SignatureRunner")
+            .field("key", &self.key)
+            .field("inputs", &self.input_names())
+            .field("outputs", &self.output_names())
+            .finish()
+    }
+}
+
+impl<'i> SignatureRunner<'i> {
+    pub(crate) fn new(interpreter_ptr: *const TfLiteInterpreter, key: &str) -> Result<Self> {
+        let key_cstring =
+            CString::new(key).map_err(|_| Error::unknown_signature_key(key, Vec::new()))?;
+        let runner_ptr =
+            unsafe { TfLiteInterpreterGetSignatureRunner(interpreter_ptr, key_cstring.as_ptr()) };
+        if runner_ptr.is_null() {
+            return Err(Error::new(ErrorKind::FailedToCreateSignatureRunner));
+        }
+        Ok(SignatureRunner {
+            key: key.to_string(),
+            runner_ptr,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Returns the key of the signature.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns the number of inputs of the signature.
+    pub fn input_count(&self) -> usize {
+        unsafe { TfLiteSignatureRunnerGetInputCount(self.runner_ptr) }
+    }
+
+    /// Returns the number of outputs of the signature.
+    pub fn output_count(&self) -> usize {
+        unsafe { TfLiteSignatureRunnerGetOutputCount(self.runner_ptr) }
+    }
+
+    /// Returns the names of the inputs of the signature, in order.
+    pub fn input_names(&self) -> Vec<String> {
+        (0..self.input_count())
+            .map(|index| unsafe {
+                name_from_raw(TfLiteSignatureRunnerGetInputName(
+                    self.runner_ptr,
+                    index as i32,
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns the names of the outputs of the signature, in order.
+    pub fn output_names(&self) -> Vec<String> {
+        (0..self.output_count())
+            .map(|index| unsafe {
+                name_from_raw(TfLiteSignatureRunnerGetOutputName(
+                    self.runner_ptr,
+                    index as i32,
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns the input [`Tensor`] with the given signature `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if no input has the given name or
+    /// [`SignatureRunner::allocate_tensors()`] was not called before calling this.
+    pub fn input(&self, name: &str) -> Result<Tensor> {
+        let tensor_ptr = self.input_ptr(name)?;
+        Tensor::from_raw(tensor_ptr).map_err(|error| {
+            if error.kind() == ErrorKind::ReadTensorError {
+                Error::new(ErrorKind::AllocateTensorsRequired)
+            } else {
+                error
+            }
+        })
+    }
+
+    /// Returns the output [`Tensor`] with the given signature `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if no output has the given name or [`SignatureRunner::invoke()`] was not
+    /// called before calling this.
+    pub fn output(&self, name: &str) -> Result<Tensor> {
+        let names = self.output_names();
+        if !names.iter().any(|output_name| output_name == name) {
+            return Err(Error::unknown_tensor_name(name, names));
+        }
+        let name_cstring = CString::new(name).unwrap();
+        let tensor_ptr =
+            unsafe { TfLiteSignatureRunnerGetOutputTensor(self.runner_ptr, name_cstring.as_ptr()) };
+        Tensor::from_raw(tensor_ptr as *mut TfLiteTensor).map_err(|error| {
+            if error.kind() == ErrorKind::ReadTensorError {
+                Error::new(ErrorKind::InvokeInterpreterRequired)
+            } else {
+                error
+            }
+        })
+    }
+
+    /// Resizes the input with the given signature `name` to `shape`.
+    ///
+    /// - Note: The client **must** call [`SignatureRunner::allocate_tensors()`] after resizing
+    /// inputs and before invoking the subgraph.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if no input has the given name or TensorFlow Lite C fails internally.
+    pub fn resize_input(&self, name: &str, shape: Shape) -> Result<()> {
+        let index = self.input_index(name)?;
+        let dims = shape
+            .dimensions()
+            .iter()
+            .map(|v| *v as i32)
+            .collect::<Vec<i32>>();
+        let name_cstring = CString::new(name).unwrap();
+        let status = unsafe {
+            TfLiteSignatureRunnerResizeInputTensor(
+                self.runner_ptr,
+                name_cstring.as_ptr(),
+                dims.as_ptr() as *const c_int,
+                dims.len() as i32,
+            )
+        };
+        if status == TfLiteStatus_kTfLiteOk {
+            Ok(())
+        } else {
+            Err(Error::new(ErrorKind::FailedToResizeInputTensor(index)))
+        }
+    }
+
+    /// Allocates memory for all tensors of the subgraph of the signature.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if TensorFlow Lite C fails to allocate memory for the tensors.
+    pub fn allocate_tensors(&self) -> Result<()> {
+        if TfLiteStatus_kTfLiteOk
+            != unsafe { TfLiteSignatureRunnerAllocateTensors(self.runner_ptr) }
+        {
+            return Err(Error::new(ErrorKind::FailedToAllocateTensors));
+        }
+        Ok(())
+    }
+
+    /// Copies the given `data` to the input with the given signature `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if no input has the given name, byte count of the data does not match
+    /// the buffer size of the input tensor or TensorFlow Lite C fails internally.
+    pub fn copy<T>(&self, data: &[T], name: &str) -> Result<()> {
+        let tensor_ptr = self.input_ptr(name)?;
+        let byte_count = std::mem::size_of_val(data);
+        unsafe {
+            let required = TfLiteTensorByteSize(tensor_ptr);
+            if byte_count != required {
+                return Err(Error::new(ErrorKind::InvalidTensorDataCount(
+                    byte_count, required,
+                )));
+            }
+            let status =
+                TfLiteTensorCopyFromBuffer(tensor_ptr, data.as_ptr() as *const c_void, byte_count);
+            if status != TfLiteStatus_kTfLiteOk {
+                Err(Error::new(ErrorKind::FailedToCopyDataToInputTensor))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    /// Invokes the subgraph of the signature.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if TensorFlow Lite C fails to invoke, e.g. because
+    /// [`SignatureRunner::allocate_tensors()`] was not called, or the invocation is cancelled.
+    pub fn invoke(&self) -> Result<()> {
+        let status = unsafe { TfLiteSignatureRunnerInvoke(self.runner_ptr) };
+        if status == TfLiteStatus_kTfLiteOk {
+            Ok(())
+        } else if status == TfLiteStatus_kTfLiteCancelled {
+            Err(Error::new(ErrorKind::InvokeCancelled))
+        } else {
+            Err(Error::new(ErrorKind::FailedToInvokeSignatureRunner))
+        }
+    }
+
+    fn input_index(&self, name: &str) -> Result<usize> {
+        let names = self.input_names();
+        names
+            .iter()
+            .position(|input_name| input_name == name)
+            .ok_or_else(|| Error::unknown_tensor_name(name, names.clone()))
+    }
+
+    fn input_ptr(&self, name: &str) -> Result<*mut TfLiteTensor> {
+        self.input_index(name)?;
+        let name_cstring = CString::new(name).unwrap();
+        Ok(unsafe { TfLiteSignatureRunnerGetInputTensor(self.runner_ptr, name_cstring.as_ptr()) })
+    }
+}
+
+impl Drop for SignatureRunner<'_> {
+    fn drop(&mut self) {
+        unsafe { TfLiteSignatureRunnerDelete(self.runner_ptr) }
+    }
+}
+
+unsafe fn name_from_raw(name_ptr: *const std::os::raw::c_char) -> String {
+    if name_ptr.is_null() {
+        String::new()
+    } else {
+        CStr::from_ptr(name_ptr).to_string_lossy().into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::Interpreter;
+    use crate::model::Model;
+    use crate::tensor::Shape;
+    use crate::ErrorKind;
+
+    const MODEL_PATH: &str = "tests/add.bin";
+
+    #[test]
+    fn test_signatures() {
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let interpreter = Interpreter::new(&model, None).expect("Cannot create interpreter!");
+        let keys = interpreter.signature_keys();
+        assert_eq!(keys.len(), interpreter.signature_count());
+
+        let error = interpreter.signature_runner("missing").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::UnknownSignatureKey);
+        assert_eq!(error.requested_name(), Some("missing"));
+        assert_eq!(error.available_names(), keys.as_slice());
+
+        for key in keys.iter() {
+            let runner = interpreter.signature_runner(key).unwrap();
+            assert_eq!(runner.key(), key);
+            assert_eq!(runner.input_names().len(), runner.input_count());
+            assert_eq!(runner.output_names().len(), runner.output_count());
+            let error = runner.input("missing").unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::UnknownTensorName);
+            assert_eq!(error.available_names(), runner.input_names().as_slice());
+        }
+    }
+    #[test]
+    fn test_signature_runner() {
+        let model = Model::new("tests/signature.bin").expect("Cannot load model from file!");
+        let interpreter = Interpreter::new(&model, None).expect("Cannot create interpreter!");
+        assert_eq!(interpreter.signature_keys(), vec!["add".to_string()]);
+
+        let runner = interpreter.signature_runner("add").unwrap();
+        assert_eq!(runner.input_names(), vec!["x".to_string(), "y".to_string()]);
+        assert_eq!(runner.output_names(), vec!["sum".to_string()]);
+        let error = runner.output("serving_x:0").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::UnknownTensorName);
+        assert_eq!(error.available_names(), &["sum".to_string()]);
+
+        runner.resize_input("x", Shape::new(vec![2, 4])).unwrap();
+        runner.resize_input("y", Shape::new(vec![2, 4])).unwrap();
+        runner.allocate_tensors().unwrap();
+        assert_eq!(runner.input("x").unwrap().shape().dimensions(), &vec![2, 4]);
+
+        let x = (0..8).map(|x| x as f32).collect::<Vec<f32>>();
+        let y = (0..8).map(|y| (y * 10) as f32).collect::<Vec<f32>>();
+        runner.copy(&x, "x").unwrap();
+        runner.copy(&y, "y").unwrap();
+        let error = runner.copy(&x[..4], "y").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidTensorDataCount(16, 32));
+        runner.invoke().unwrap();
+
+        let output = runner.output("sum").unwrap();
+        assert_eq!(output.shape().dimensions(), &vec![2, 4]);
+        let expected = x
+            .iter()
+            .zip(y.iter())
+            .map(|(x, y)| x + y)
+            .collect::<Vec<f32>>();
+        assert_eq!(output.data::<f32>(), expected.as_slice());
+    }
+}
@@ -1,4 +1,5 @@
 //! TensorFlow Lite input or output [`Tensor`] associated with an interpreter.
+use std::convert::TryFrom;
 use std::ffi::{c_void, CStr};
 
 use crate::bindings;
@@ -7,6 +8,34 @@ use crate::{Error, ErrorKind, Result};
 use std::fmt::{Debug, Formatter};
 use std::marker::PhantomData;
 
+/// The alignment in bytes that TensorFlow Lite requires for tensor buffers.
+///
+/// Buffers bound via [`CustomAllocations::set_input_allocation()`][crate::interpreter::CustomAllocations::set_input_allocation]
+/// or [`CustomAllocations::set_output_allocation()`][crate::interpreter::CustomAllocations::set_output_allocation]
+/// must start at an address which is a multiple of this value.
+pub const TENSOR_ALIGNMENT: usize = 64;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// The element types whose values can be read from and written to raw tensor memory.
+///
+/// The trait is sealed and implemented only for primitive numeric types, which have no
+/// padding and for which every bit pattern is a valid value.
+pub trait TensorElement: sealed::Sealed + Copy + 'static {}
+
+macro_rules! impl_tensor_element {
+    ($($element:ty),*) => {
+        $(
+            impl sealed::Sealed for $element {}
+            impl TensorElement for $element {}
+        )*
+    };
+}
+
+impl_tensor_element!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64);
+
 /// Parameters that determine the mapping of quantized values to real values.
 ///
 /// Quantized values can be mapped to float values using the following conversion:
@@ -22,6 +51,26 @@ pub struct QuantizationParameters {
     pub zero_point: i32,
 }
 
+impl QuantizationParameters {
+    /// Returns the quantization parameters of the tensor at `tensor_ptr` of `data_type`, if it
+    /// is quantized.
+    unsafe fn from_raw(
+        tensor_ptr: *const TfLiteTensor,
+        data_type: DataType,
+    ) -> Option<QuantizationParameters> {
+        let parameters = TfLiteTensorQuantizationParams(tensor_ptr);
+        if parameters.scale == 0.0 || (data_type != DataType::Uint8 && data_type != DataType::Int8)
+        {
+            None
+        } else {
+            Some(QuantizationParameters {
+                scale: parameters.scale,
+                zero_point: parameters.zero_point,
+            })
+        }
+    }
+}
+
 /// The supported [`Tensor`] data types.
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
 pub enum DataType {
@@ -43,6 +92,8 @@ pub enum DataType {
     Float32,
     /// A 64-bit double precision floating point.
     Float64,
+    /// A handle of a resource (e.g. a resource variable), stored as a 32-bit signed integer.
+    Resource,
 }
 
 impl DataType {
@@ -66,6 +117,7 @@ impl DataType {
             bindings::TfLiteType_kTfLiteFloat16 => Some(DataType::Float16),
             bindings::TfLiteType_kTfLiteFloat32 => Some(DataType::Float32),
             bindings::TfLiteType_kTfLiteFloat64 => Some(DataType::Float64),
+            bindings::TfLiteType_kTfLiteResource => Some(DataType::Resource),
             _ => None,
         }
     }
@@ -186,17 +238,7 @@ impl<'a> Tensor<'a> {
                 data_ptr,
                 data_length,
             };
-            let quantization_parameters_ptr = TfLiteTensorQuantizationParams(tensor_ptr);
-            let scale = quantization_parameters_ptr.scale;
-            let quantization_parameters =
-                if scale == 0.0 || (data_type != DataType::Uint8 && data_type != DataType::Int8) {
-                    None
-                } else {
-                    Some(QuantizationParameters {
-                        scale: quantization_parameters_ptr.scale,
-                        zero_point: quantization_parameters_ptr.zero_point,
-                    })
-                };
+            let quantization_parameters = QuantizationParameters::from_raw(tensor_ptr, data_type);
             Ok(Tensor {
                 name,
                 data_type,
@@ -284,3 +326,111 @@ impl<'a> Tensor<'a> {
         self.name.as_str()
     }
 }
+
+/// The description of an input or output tensor, which is available before its memory is
+/// allocated, see [`Interpreter::input_details()`][crate::interpreter::Interpreter::input_details].
+#[derive(Clone, PartialEq, Debug)]
+pub struct TensorInfo {
+    /// The name of the tensor.
+    name: String,
+
+    /// The data type of the tensor.
+    data_type: DataType,
+
+    /// The current shape of the tensor.
+    shape: Shape,
+
+    /// The dimensions of the tensor in the model, [`None`] for dynamic ones.
+    shape_signature: Vec<Option<usize>>,
+
+    /// The quantization parameters for the tensor if using a quantized model.
+    quantization_parameters: Option<QuantizationParameters>,
+}
+
+impl TensorInfo {
+    pub(crate) fn from_raw(tensor_ptr: *const TfLiteTensor) -> Result<TensorInfo> {
+        unsafe {
+            if tensor_ptr.is_null() {
+                return Err(Error::new(ErrorKind::ReadTensorError));
+            }
+            let name_ptr = TfLiteTensorName(tensor_ptr);
+            if name_ptr.is_null() {
+                return Err(Error::new(ErrorKind::ReadTensorError));
+            }
+            let name = CStr::from_ptr(name_ptr).to_string_lossy().into_owned();
+            let data_type = DataType::new(TfLiteTensorType(tensor_ptr))
+                .ok_or_else(|| Error::new(ErrorKind::InvalidTensorDataType))?;
+
+            let rank = TfLiteTensorNumDims(tensor_ptr);
+            let dimensions = (0..rank)
+                .map(|i| TfLiteTensorDim(tensor_ptr, i) as usize)
+                .collect::<Vec<_>>();
+            let shape_signature = TensorInfo::shape_signature_from_raw(tensor_ptr)
+                .unwrap_or_else(|| dimensions.iter().map(|&dim| Some(dim)).collect());
+            Ok(TensorInfo {
+                name,
+                data_type,
+                shape: Shape::new(dimensions),
+                shape_signature,
+                quantization_parameters: QuantizationParameters::from_raw(tensor_ptr, data_type),
+            })
+        }
+    }
+
+    /// Returns the dimensions of the tensor in the model, which TensorFlow Lite C reports
+    /// as -1 if they are dynamic.
+    unsafe fn shape_signature_from_raw(
+        tensor_ptr: *const TfLiteTensor,
+    ) -> Option<Vec<Option<usize>>> {
+        let opaque_tensor_ptr = tensor_ptr as *const TfLiteOpaqueTensor;
+        let mut rank = 0;
+        if TfLiteOpaqueTensorGetNumDimsSignature(opaque_tensor_ptr, &mut rank)
+            != TfLiteStatus_kTfLiteOk
+        {
+            return None;
+        }
+        (0..rank)
+            .map(|i| {
+                let mut dim = 0;
+                if TfLiteOpaqueTensorGetDimSignature(opaque_tensor_ptr, i, &mut dim)
+                    != TfLiteStatus_kTfLiteOk
+                {
+                    return None;
+                }
+                Some(usize::try_from(dim).ok())
+            })
+            .collect()
+    }
+
+    /// Returns name of the tensor.
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns [data type][`DataType`] of the tensor.
+    pub fn data_type(&self) -> DataType {
+        self.data_type
+    }
+
+    /// Returns the current [`Shape`] of the tensor, which reflects
+    /// [`Interpreter::resize_input()`][crate::interpreter::Interpreter::resize_input] calls.
+    pub fn shape(&self) -> &Shape {
+        &self.shape
+    }
+
+    /// Returns the dimensions of the tensor in the model, where dynamic dimensions
+    /// (e.g. the batch size) are [`None`].
+    pub fn shape_signature(&self) -> &[Option<usize>] {
+        &self.shape_signature
+    }
+
+    /// Returns whether the tensor has a dynamic dimension in the model.
+    pub fn is_dynamic(&self) -> bool {
+        self.shape_signature.iter().any(Option::is_none)
+    }
+
+    /// Returns optional [`QuantizationParameters`] of the tensor.
+    pub fn quantization_parameters(&self) -> Option<QuantizationParameters> {
+        self.quantization_parameters
+    }
+}
@@ -0,0 +1,220 @@
+//! A process-level [`ThreadPool`] limiting CPU parallelism of several interpreters.
+use std::sync::{Arc, Condvar, Mutex};
+
+use crate::{Error, ErrorKind, Result};
+
+/// A budget of CPU threads shared by several [`Interpreter`][crate::interpreter::Interpreter]s.
+///
+/// Despite its name, it is a limiter rather than a pool of worker threads: TensorFlow Lite C
+/// does not allow interpreters to share worker threads, so each interpreter, including its
+/// XNNPACK delegate, keeps its own threads. They are only busy while holding threads of the
+/// budget, though.
+///
+/// Each interpreter attached with
+/// [`OptionsBuilder::thread_pool()`][crate::interpreter::OptionsBuilder::thread_pool]
+/// runs its default CPU backend and XNNPACK delegate with at most
+/// [`ThreadPool::thread_count()`] threads, and every
+/// [`Interpreter::invoke()`][crate::interpreter::Interpreter::invoke] call waits until
+/// enough threads of the pool are free. Hence, the total number of threads running
+/// inference at once never exceeds the size of the pool, however many interpreters are used.
+///
+/// Invocations acquire threads in the order they arrive, so an invocation needing many
+/// threads is not starved by smaller ones behind it, but those wait for it instead.
+///
+/// - Note: An interpreter with `thread_count` -1 uses the whole pool, so its invocations run
+/// alone. Set an explicit thread count to let interpreters run concurrently.
+///
+/// Cloning a `ThreadPool` returns a handle to the same pool.
+///
+/// # Examples
+///
+/// ```
+/// use tflitec::interpreter::{Interpreter, Options};
+/// use tflitec::model::Model;
+/// use tflitec::thread_pool::ThreadPool;
+/// let pool = ThreadPool::new(4)?;
+/// let options = Options::builder()
+///     .thread_count(2)
+///     .thread_pool(pool.clone())
+///     .build()?;
+/// let model = Model::new("tests/add.bin")?;
+/// let interpreter = Interpreter::new(&model, Some(options))?;
+/// interpreter.allocate_tensors()?;
+/// interpreter.invoke()?;
+/// assert_eq!(pool.available_thread_count(), 4);
+/// # Ok::<(), tflitec::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct ThreadPool {
+    inner: Arc<ThreadPoolInner>,
+}
+
+#[derive(Debug)]
+struct ThreadPoolInner {
+    /// The total number of threads in the pool.
+    thread_count: usize,
+
+    /// The threads which are not held by an invocation and the queue of waiting ones.
+    state: Mutex<ThreadPoolState>,
+
+    /// Notified when threads are released or the queue advances.
+    released: Condvar,
+}
+
+#[derive(Debug)]
+struct ThreadPoolState {
+    /// The number of threads which are not held by an invocation.
+    available: usize,
+
+    /// The ticket of the next invocation to wait for threads.
+    next_ticket: u64,
+
+    /// The ticket of the invocation whose turn it is to take threads.
+    serving_ticket: u64,
+}
+
+impl ThreadPool {
+    /// Creates a new pool of `thread_count` threads.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if `thread_count` is zero or does not fit in `i32`.
+    pub fn new(thread_count: usize) -> Result<ThreadPool> {
+        if thread_count == 0 || thread_count > i32::MAX as usize {
+            return Err(Error::new(ErrorKind::InvalidThreadCount(
+                thread_count.min(i32::MAX as usize) as i32,
+            )));
+        }
+        Ok(ThreadPool {
+            inner: Arc::new(ThreadPoolInner {
+                thread_count,
+                state: Mutex::new(ThreadPoolState {
+                    available: thread_count,
+                    next_ticket: 0,
+                    serving_ticket: 0,
+                }),
+                released: Condvar::new(),
+            }),
+        })
+    }
+
+    /// Returns the total number of threads in the pool.
+    pub fn thread_count(&self) -> usize {
+        self.inner.thread_count
+    }
+
+    /// Returns the number of threads which are currently not in use.
+    pub fn available_thread_count(&self) -> usize {
+        self.inner.state.lock().unwrap().available
+    }
+
+    /// Returns the number of threads an interpreter with the given `thread_count` option uses.
+    ///
+    /// `-1` (implementation-defined) uses the whole pool and `0` disables multithreading.
+    pub(crate) fn effective_thread_count(&self, thread_count: i32) -> usize {
+        match thread_count {
+            -1 => self.inner.thread_count,
+            0 => 1,
+            thread_count => (thread_count as usize).min(self.inner.thread_count),
+        }
+    }
+
+    /// Blocks until all earlier callers took their threads and `thread_count` threads are
+    /// available, and holds them until the returned guard is dropped.
+    pub(crate) fn acquire(&self, thread_count: usize) -> ThreadPoolGuard {
+        let thread_count = thread_count.min(self.inner.thread_count);
+        let mut state = self.inner.state.lock().unwrap();
+        let ticket = state.next_ticket;
+        state.next_ticket += 1;
+        while state.serving_ticket != ticket || state.available < thread_count {
+            state = self.inner.released.wait(state).unwrap();
+        }
+        state.available -= thread_count;
+        state.serving_ticket += 1;
+        drop(state);
+        // The next caller in the queue may find enough threads as well.
+        self.inner.released.notify_all();
+        ThreadPoolGuard {
+            pool: self,
+            thread_count,
+        }
+    }
+}
+
+/// Threads held from a [`ThreadPool`], released when dropped.
+pub(crate) struct ThreadPoolGuard<'a> {
+    pool: &'a ThreadPool,
+    thread_count: usize,
+}
+
+impl Drop for ThreadPoolGuard<'_> {
+    fn drop(&mut self) {
+        self.pool.inner.state.lock().unwrap().available += self.thread_count;
+        self.pool.inner.released.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::thread_pool::ThreadPool;
+    use crate::ErrorKind;
+
+    #[test]
+    fn test_thread_pool_acquire() {
+        assert_eq!(
+            ErrorKind::InvalidThreadCount(0),
+            ThreadPool::new(0).err().unwrap().kind()
+        );
+        let pool = ThreadPool::new(4).unwrap();
+        assert_eq!(pool.effective_thread_count(-1), 4);
+        assert_eq!(pool.effective_thread_count(0), 1);
+        assert_eq!(pool.effective_thread_count(8), 4);
+
+        let guard = pool.acquire(3);
+        assert_eq!(pool.available_thread_count(), 1);
+        let handle = {
+            let pool = pool.clone();
+            std::thread::spawn(move || {
+                // Blocks until the first guard is dropped.
+                let _guard = pool.acquire(2);
+                assert!(pool.available_thread_count() <= 2);
+            })
+        };
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        assert_eq!(pool.available_thread_count(), 1);
+        drop(guard);
+        handle.join().unwrap();
+        assert_eq!(pool.available_thread_count(), 4);
+    }
+
+    #[test]
+    fn test_thread_pool_acquire_in_order() {
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        let pool = ThreadPool::new(4).unwrap();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let guard = pool.acquire(2);
+        let spawn = |name: &'static str, thread_count: usize| {
+            let pool = pool.clone();
+            let order = order.clone();
+            let handle = std::thread::spawn(move || {
+                let _guard = pool.acquire(thread_count);
+                order.lock().unwrap().push(name);
+                std::thread::sleep(Duration::from_millis(10));
+            });
+            std::thread::sleep(Duration::from_millis(20));
+            handle
+        };
+        // The whole pool is requested first, so the smaller request behind it waits even
+        // though enough threads are available for it.
+        let whole = spawn("whole", 4);
+        let small = spawn("small", 1);
+        assert!(order.lock().unwrap().is_empty());
+        drop(guard);
+        whole.join().unwrap();
+        small.join().unwrap();
+        assert_eq!(*order.lock().unwrap(), vec!["whole", "small"]);
+        assert_eq!(pool.available_thread_count(), 4);
+    }
+}
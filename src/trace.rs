@@ -0,0 +1,398 @@
+//! A [`TraceRecorder`] exporting inference timelines in Chrome Trace Event format.
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::profiler::{OpEvent, OpEventTracker, TelemetryProfiler};
+
+/// The category of interpreter lifecycle events, e.g. `invoke`.
+pub const INTERPRETER_CATEGORY: &str = "interpreter";
+
+/// The category of operator events.
+pub const OPERATOR_CATEGORY: &str = "operator";
+
+/// A complete event of a [`TraceRecorder`].
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct TraceEvent {
+    /// The name of the event, e.g. `invoke` or the name of an operator.
+    pub name: String,
+
+    /// The category of the event, [`INTERPRETER_CATEGORY`] or [`OPERATOR_CATEGORY`].
+    pub category: &'static str,
+
+    /// The start time of the event relative to the creation of the recorder.
+    pub start: Duration,
+
+    /// The duration of the event.
+    pub duration: Duration,
+
+    /// The identifier of the thread the event occurred on, unique in the process.
+    pub thread_id: u64,
+
+    /// Additional key-value arguments of the event.
+    pub args: Vec<(String, String)>,
+}
+
+#[derive(Debug, Default)]
+struct TraceState {
+    /// The operator invocations which have not ended yet.
+    open_op_events: OpEventTracker,
+
+    /// The recorded events in the order they ended.
+    events: Vec<TraceEvent>,
+
+    /// The names of the threads events occurred on, keyed by thread identifier.
+    thread_names: HashMap<u64, String>,
+}
+
+/// A recorder of interpreter lifecycle and operator events which can be written in
+/// [Chrome Trace Event format], to be opened in `chrome://tracing` or [Perfetto].
+///
+/// Attach it with
+/// [`OptionsBuilder::trace_recorder()`][crate::interpreter::OptionsBuilder::trace_recorder] to
+/// record [`Interpreter::new()`][crate::interpreter::Interpreter::new],
+/// [`Interpreter::resize_input()`][crate::interpreter::Interpreter::resize_input],
+/// [`Interpreter::allocate_tensors()`][crate::interpreter::Interpreter::allocate_tensors] and
+/// [`Interpreter::invoke()`][crate::interpreter::Interpreter::invoke] calls. Operator events are
+/// recorded if it is also attached with
+/// [`OptionsBuilder::telemetry_profiler()`][crate::interpreter::OptionsBuilder::telemetry_profiler].
+/// A recorder can be shared by several interpreters running on several threads.
+///
+/// [Chrome Trace Event format]: https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU
+/// [Perfetto]: https://ui.perfetto.dev
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use tflitec::interpreter::{Interpreter, Options};
+/// use tflitec::model::Model;
+/// use tflitec::trace::TraceRecorder;
+/// let recorder = Arc::new(TraceRecorder::new());
+/// let options = Options::builder()
+///     .trace_recorder(recorder.clone())
+///     .telemetry_profiler(recorder.clone())
+///     .build()?;
+/// let model = Model::new("tests/add.bin")?;
+/// let interpreter = Interpreter::new(&model, Some(options))?;
+/// interpreter.allocate_tensors()?;
+/// interpreter.invoke()?;
+/// let json = recorder.to_json();
+/// assert!(json.contains("\"name\":\"invoke\""));
+/// # Ok::<(), tflitec::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct TraceRecorder {
+    /// The time all timestamps are relative to.
+    origin: Instant,
+    state: Mutex<TraceState>,
+}
+
+impl Default for TraceRecorder {
+    fn default() -> Self {
+        TraceRecorder {
+            origin: Instant::now(),
+            state: Mutex::new(TraceState::default()),
+        }
+    }
+}
+
+impl TraceRecorder {
+    /// Creates a new recorder without any recorded event.
+    pub fn new() -> TraceRecorder {
+        TraceRecorder::default()
+    }
+
+    /// Returns the recorded events in the order they ended.
+    pub fn events(&self) -> Vec<TraceEvent> {
+        self.state.lock().unwrap().events.clone()
+    }
+
+    /// Removes all recorded events, including operator invocations which have not ended yet.
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.open_op_events.clear();
+        state.events.clear();
+    }
+
+    /// Starts an interpreter lifecycle event, which is recorded when the returned span is
+    /// dropped.
+    pub(crate) fn span(&self, name: &'static str, interpreter_id: usize) -> TraceSpan<'_> {
+        TraceSpan {
+            recorder: self,
+            name,
+            interpreter_id,
+            start: Instant::now(),
+        }
+    }
+
+    fn record(&self, event: TraceEvent) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(name) = std::thread::current().name() {
+            state
+                .thread_names
+                .entry(event.thread_id)
+                .or_insert_with(|| name.to_owned());
+        }
+        state.events.push(event);
+    }
+
+    /// Records an operator invocation, which ended on the current thread.
+    fn record_op_event(&self, event: OpEvent) {
+        let mut args = vec![
+            ("op_index".to_string(), event.op_index.to_string()),
+            (
+                "subgraph_index".to_string(),
+                event.subgraph_index.to_string(),
+            ),
+        ];
+        if let Some(delegate) = event.delegate {
+            args.push(("delegate".to_string(), delegate));
+        }
+        self.record(TraceEvent {
+            name: event.op_name,
+            category: OPERATOR_CATEGORY,
+            start: self.since_origin(event.start),
+            duration: event.duration,
+            thread_id: current_thread_id(),
+            args,
+        });
+    }
+
+    fn since_origin(&self, instant: Instant) -> Duration {
+        instant.saturating_duration_since(self.origin)
+    }
+
+    /// Returns the recorded events as a Chrome Trace Event JSON object.
+    pub fn to_json(&self) -> String {
+        let state = self.state.lock().unwrap();
+        let pid = std::process::id();
+        let mut events = Vec::with_capacity(state.thread_names.len() + state.events.len());
+        let mut thread_names = state.thread_names.iter().collect::<Vec<_>>();
+        thread_names.sort();
+        for (thread_id, name) in thread_names {
+            events.push(format!(
+                "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":{},\"tid\":{},\"args\":{{\"name\":{}}}}}",
+                pid,
+                thread_id,
+                json_string(name)
+            ));
+        }
+        for event in state.events.iter() {
+            let mut json = format!(
+                "{{\"name\":{},\"cat\":\"{}\",\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\"pid\":{},\"tid\":{},\"args\":{{",
+                json_string(&event.name),
+                event.category,
+                event.start.as_secs_f64() * 1e6,
+                event.duration.as_secs_f64() * 1e6,
+                pid,
+                event.thread_id
+            );
+            for (i, (key, value)) in event.args.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                let _ = write!(json, "{}:{}", json_string(key), json_string(value));
+            }
+            json.push_str("}}");
+            events.push(json);
+        }
+        format!(
+            "{{\"traceEvents\":[{}],\"displayTimeUnit\":\"ms\"}}",
+            events.join(",")
+        )
+    }
+
+    /// Writes the recorded events as a Chrome Trace Event JSON object to the given `writer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if writing to `writer` fails.
+    pub fn write_json<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writer.write_all(self.to_json().as_bytes())
+    }
+}
+
+impl TelemetryProfiler for TraceRecorder {
+    fn begin_op_invoke(&self, op_name: &str, op_index: i64, subgraph_index: i64) -> u32 {
+        let mut state = self.state.lock().unwrap();
+        state
+            .open_op_events
+            .begin(op_name, None, op_index, subgraph_index)
+    }
+
+    fn begin_delegate_op_invoke(
+        &self,
+        op_name: &str,
+        delegate: &str,
+        op_index: i64,
+        subgraph_index: i64,
+    ) -> u32 {
+        let mut state = self.state.lock().unwrap();
+        state
+            .open_op_events
+            .begin(op_name, Some(delegate), op_index, subgraph_index)
+    }
+
+    fn end_op_invoke(&self, event_handle: u32) {
+        let event = self.state.lock().unwrap().open_op_events.end(event_handle);
+        if let Some(event) = event {
+            self.record_op_event(event);
+        }
+    }
+
+    fn report_op_invoke(&self, op_name: &str, elapsed_us: u64, op_index: i64, subgraph_index: i64) {
+        self.record_op_event(OpEvent::complete(
+            op_name,
+            None,
+            elapsed_us,
+            op_index,
+            subgraph_index,
+        ));
+    }
+
+    fn report_delegate_op_invoke(
+        &self,
+        op_name: &str,
+        delegate: &str,
+        elapsed_us: u64,
+        op_index: i64,
+        subgraph_index: i64,
+    ) {
+        self.record_op_event(OpEvent::complete(
+            op_name,
+            Some(delegate),
+            elapsed_us,
+            op_index,
+            subgraph_index,
+        ));
+    }
+}
+
+/// An interpreter lifecycle event of a [`TraceRecorder`], recorded when dropped.
+pub(crate) struct TraceSpan<'a> {
+    recorder: &'a TraceRecorder,
+    name: &'static str,
+    interpreter_id: usize,
+    start: Instant,
+}
+
+impl Drop for TraceSpan<'_> {
+    fn drop(&mut self) {
+        let duration = self.start.elapsed();
+        self.recorder.record(TraceEvent {
+            name: self.name.to_owned(),
+            category: INTERPRETER_CATEGORY,
+            start: self.recorder.since_origin(self.start),
+            duration,
+            thread_id: current_thread_id(),
+            args: vec![("interpreter".to_string(), self.interpreter_id.to_string())],
+        });
+    }
+}
+
+/// Returns the identifier of the current thread, unique in the process.
+fn current_thread_id() -> u64 {
+    static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(1);
+    thread_local! {
+        static THREAD_ID: u64 = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+    }
+    THREAD_ID.with(|thread_id| *thread_id)
+}
+
+/// Returns the given string as a JSON string literal.
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::{Interpreter, Options};
+    use crate::model::Model;
+    use crate::tensor;
+    use crate::trace::{json_string, TraceRecorder, INTERPRETER_CATEGORY, OPERATOR_CATEGORY};
+    use std::sync::Arc;
+
+    const MODEL_PATH: &str = "tests/add.bin";
+
+    #[test]
+    fn test_trace_recorder() {
+        assert_eq!(json_string("a\"b\\\n"), "\"a\\\"b\\\\\\n\"");
+
+        let recorder = Arc::new(TraceRecorder::new());
+        let handles = (0..2)
+            .map(|i| {
+                let recorder = recorder.clone();
+                std::thread::Builder::new()
+                    .name(format!("worker-{}", i))
+                    .spawn(move || {
+                        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+                        let options = Options::builder()
+                            .trace_recorder(recorder.clone())
+                            .telemetry_profiler(recorder)
+                            .build()
+                            .expect("Cannot build options");
+                        let interpreter = Interpreter::new(&model, Some(options))
+                            .expect("Cannot create interpreter!");
+                        interpreter
+                            .resize_input(0, tensor::Shape::new(vec![1, 8, 8, 3]))
+                            .expect("Cannot resize input");
+                        interpreter
+                            .allocate_tensors()
+                            .expect("Cannot allocate tensors");
+                        assert!(interpreter.invoke().is_ok());
+                    })
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .for_each(|handle| handle.join().unwrap());
+
+        let events = recorder.events();
+        let invokes = events
+            .iter()
+            .filter(|event| event.category == INTERPRETER_CATEGORY && event.name == "invoke")
+            .collect::<Vec<_>>();
+        assert_eq!(invokes.len(), 2);
+        assert_ne!(invokes[0].thread_id, invokes[1].thread_id);
+        assert!(events.iter().any(
+            |event| event.category == INTERPRETER_CATEGORY && event.name == "allocate_tensors"
+        ));
+        assert!(events
+            .iter()
+            .any(|event| event.category == INTERPRETER_CATEGORY && event.name == "resize_input"));
+        assert!(events
+            .iter()
+            .any(|event| event.category == OPERATOR_CATEGORY && event.name == "ADD"));
+
+        let json = recorder.to_json();
+        assert!(json.starts_with("{\"traceEvents\":["));
+        assert!(json.contains("\"ph\":\"X\""));
+        assert!(json.contains("\"name\":\"worker-0\""));
+        let mut buffer = Vec::new();
+        recorder.write_json(&mut buffer).unwrap();
+        assert_eq!(buffer, json.into_bytes());
+        recorder.clear();
+        assert!(recorder.events().is_empty());
+    }
+}
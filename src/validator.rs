@@ -0,0 +1,878 @@
+//! A [`ModelValidator`] restricting the operators a model may use.
+use std::collections::HashMap;
+use std::ffi::{c_void, CStr};
+use std::ops::{Bound, RangeBounds};
+use std::os::raw::{c_char, c_int};
+
+use crate::bindings::*;
+use crate::interpreter::VaListArg;
+use crate::model::Model;
+use crate::{Error, ErrorKind, Result};
+
+/// The builtin operators with their names in the TensorFlow Lite schema.
+const BUILTIN_OPERATORS: &[(TfLiteBuiltinOperator, &str)] = &[
+    (TfLiteBuiltinOperator_kTfLiteBuiltinAdd, "ADD"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinAveragePool2d,
+        "AVERAGE_POOL_2D",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinConcatenation,
+        "CONCATENATION",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinConv2d, "CONV_2D"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinDepthwiseConv2d,
+        "DEPTHWISE_CONV_2D",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinDepthToSpace,
+        "DEPTH_TO_SPACE",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinDequantize, "DEQUANTIZE"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinEmbeddingLookup,
+        "EMBEDDING_LOOKUP",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinFloor, "FLOOR"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinFullyConnected,
+        "FULLY_CONNECTED",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinHashtableLookup,
+        "HASHTABLE_LOOKUP",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinL2Normalization,
+        "L2_NORMALIZATION",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinL2Pool2d, "L2_POOL_2D"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinLocalResponseNormalization,
+        "LOCAL_RESPONSE_NORMALIZATION",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinLogistic, "LOGISTIC"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinLshProjection,
+        "LSH_PROJECTION",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinLstm, "LSTM"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinMaxPool2d, "MAX_POOL_2D"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinMul, "MUL"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinRelu, "RELU"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinReluN1To1,
+        "RELU_N1_TO_1",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinRelu6, "RELU6"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinReshape, "RESHAPE"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinResizeBilinear,
+        "RESIZE_BILINEAR",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinRnn, "RNN"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinSoftmax, "SOFTMAX"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinSpaceToDepth,
+        "SPACE_TO_DEPTH",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinSvdf, "SVDF"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinTanh, "TANH"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinConcatEmbeddings,
+        "CONCAT_EMBEDDINGS",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinSkipGram, "SKIP_GRAM"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinCall, "CALL"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinCustom, "CUSTOM"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinEmbeddingLookupSparse,
+        "EMBEDDING_LOOKUP_SPARSE",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinPad, "PAD"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinUnidirectionalSequenceRnn,
+        "UNIDIRECTIONAL_SEQUENCE_RNN",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinGather, "GATHER"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinBatchToSpaceNd,
+        "BATCH_TO_SPACE_ND",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinSpaceToBatchNd,
+        "SPACE_TO_BATCH_ND",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinTranspose, "TRANSPOSE"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinMean, "MEAN"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinSub, "SUB"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinDiv, "DIV"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinSqueeze, "SQUEEZE"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinUnidirectionalSequenceLstm,
+        "UNIDIRECTIONAL_SEQUENCE_LSTM",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStridedSlice,
+        "STRIDED_SLICE",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinBidirectionalSequenceRnn,
+        "BIDIRECTIONAL_SEQUENCE_RNN",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinExp, "EXP"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinTopkV2, "TOPK_V2"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinSplit, "SPLIT"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinLogSoftmax,
+        "LOG_SOFTMAX",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinDelegate, "DELEGATE"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinBidirectionalSequenceLstm,
+        "BIDIRECTIONAL_SEQUENCE_LSTM",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinCast, "CAST"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinPrelu, "PRELU"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinMaximum, "MAXIMUM"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinArgMax, "ARG_MAX"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinMinimum, "MINIMUM"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinLess, "LESS"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinNeg, "NEG"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinPadv2, "PADV2"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinGreater, "GREATER"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinGreaterEqual,
+        "GREATER_EQUAL",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinLessEqual, "LESS_EQUAL"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinSelect, "SELECT"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinSlice, "SLICE"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinSin, "SIN"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinTransposeConv,
+        "TRANSPOSE_CONV",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinSparseToDense,
+        "SPARSE_TO_DENSE",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinTile, "TILE"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinExpandDims,
+        "EXPAND_DIMS",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinEqual, "EQUAL"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinNotEqual, "NOT_EQUAL"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinLog, "LOG"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinSum, "SUM"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinSqrt, "SQRT"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinRsqrt, "RSQRT"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinShape, "SHAPE"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinPow, "POW"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinArgMin, "ARG_MIN"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinFakeQuant, "FAKE_QUANT"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinReduceProd,
+        "REDUCE_PROD",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinReduceMax, "REDUCE_MAX"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinPack, "PACK"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinLogicalOr, "LOGICAL_OR"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinOneHot, "ONE_HOT"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinLogicalAnd,
+        "LOGICAL_AND",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinLogicalNot,
+        "LOGICAL_NOT",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinUnpack, "UNPACK"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinReduceMin, "REDUCE_MIN"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinFloorDiv, "FLOOR_DIV"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinReduceAny, "REDUCE_ANY"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinSquare, "SQUARE"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinZerosLike, "ZEROS_LIKE"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinFill, "FILL"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinFloorMod, "FLOOR_MOD"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinRange, "RANGE"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinResizeNearestNeighbor,
+        "RESIZE_NEAREST_NEIGHBOR",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinLeakyRelu, "LEAKY_RELU"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinSquaredDifference,
+        "SQUARED_DIFFERENCE",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinMirrorPad, "MIRROR_PAD"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinAbs, "ABS"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinSplitV, "SPLIT_V"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinUnique, "UNIQUE"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinCeil, "CEIL"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinReverseV2, "REVERSE_V2"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinAddN, "ADD_N"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinGatherNd, "GATHER_ND"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinCos, "COS"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinWhere, "WHERE"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinRank, "RANK"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinElu, "ELU"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinReverseSequence,
+        "REVERSE_SEQUENCE",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinMatrixDiag,
+        "MATRIX_DIAG",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinQuantize, "QUANTIZE"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinMatrixSetDiag,
+        "MATRIX_SET_DIAG",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinRound, "ROUND"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinHardSwish, "HARD_SWISH"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinIf, "IF"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinWhile, "WHILE"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinNonMaxSuppressionV4,
+        "NON_MAX_SUPPRESSION_V4",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinNonMaxSuppressionV5,
+        "NON_MAX_SUPPRESSION_V5",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinScatterNd, "SCATTER_ND"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinSelectV2, "SELECT_V2"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinDensify, "DENSIFY"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinSegmentSum,
+        "SEGMENT_SUM",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinBatchMatmul,
+        "BATCH_MATMUL",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinPlaceholderForGreaterOpCodes,
+        "PLACEHOLDER_FOR_GREATER_OP_CODES",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinCumsum, "CUMSUM"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinCallOnce, "CALL_ONCE"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinBroadcastTo,
+        "BROADCAST_TO",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinRfft2d, "RFFT2D"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinConv3d, "CONV_3D"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinImag, "IMAG"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinReal, "REAL"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinComplexAbs,
+        "COMPLEX_ABS",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinHashtable, "HASHTABLE"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinHashtableFind,
+        "HASHTABLE_FIND",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinHashtableImport,
+        "HASHTABLE_IMPORT",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinHashtableSize,
+        "HASHTABLE_SIZE",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinReduceAll, "REDUCE_ALL"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinConv3dTranspose,
+        "CONV_3D_TRANSPOSE",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinVarHandle, "VAR_HANDLE"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinReadVariable,
+        "READ_VARIABLE",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinAssignVariable,
+        "ASSIGN_VARIABLE",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinBroadcastArgs,
+        "BROADCAST_ARGS",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinRandomStandardNormal,
+        "RANDOM_STANDARD_NORMAL",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinBucketize, "BUCKETIZE"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinRandomUniform,
+        "RANDOM_UNIFORM",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinMultinomial,
+        "MULTINOMIAL",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinGelu, "GELU"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinDynamicUpdateSlice,
+        "DYNAMIC_UPDATE_SLICE",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinRelu0To1, "RELU_0_TO_1"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinUnsortedSegmentProd,
+        "UNSORTED_SEGMENT_PROD",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinUnsortedSegmentMax,
+        "UNSORTED_SEGMENT_MAX",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinUnsortedSegmentSum,
+        "UNSORTED_SEGMENT_SUM",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinAtan2, "ATAN2"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinUnsortedSegmentMin,
+        "UNSORTED_SEGMENT_MIN",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinSign, "SIGN"),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinBitcast, "BITCAST"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinBitwiseXor,
+        "BITWISE_XOR",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinRightShift,
+        "RIGHT_SHIFT",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloLogistic,
+        "STABLEHLO_LOGISTIC",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloAdd,
+        "STABLEHLO_ADD",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloDivide,
+        "STABLEHLO_DIVIDE",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloMultiply,
+        "STABLEHLO_MULTIPLY",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloMaximum,
+        "STABLEHLO_MAXIMUM",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloReshape,
+        "STABLEHLO_RESHAPE",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloClamp,
+        "STABLEHLO_CLAMP",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloConcatenate,
+        "STABLEHLO_CONCATENATE",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloBroadcastInDim,
+        "STABLEHLO_BROADCAST_IN_DIM",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloConvolution,
+        "STABLEHLO_CONVOLUTION",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloSlice,
+        "STABLEHLO_SLICE",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloCustomCall,
+        "STABLEHLO_CUSTOM_CALL",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloReduce,
+        "STABLEHLO_REDUCE",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloAbs,
+        "STABLEHLO_ABS",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloAnd,
+        "STABLEHLO_AND",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloCosine,
+        "STABLEHLO_COSINE",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloExponential,
+        "STABLEHLO_EXPONENTIAL",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloFloor,
+        "STABLEHLO_FLOOR",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloLog,
+        "STABLEHLO_LOG",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloMinimum,
+        "STABLEHLO_MINIMUM",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloNegate,
+        "STABLEHLO_NEGATE",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloOr,
+        "STABLEHLO_OR",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloPower,
+        "STABLEHLO_POWER",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloRemainder,
+        "STABLEHLO_REMAINDER",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloRsqrt,
+        "STABLEHLO_RSQRT",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloSelect,
+        "STABLEHLO_SELECT",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloSubtract,
+        "STABLEHLO_SUBTRACT",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloTanh,
+        "STABLEHLO_TANH",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloScatter,
+        "STABLEHLO_SCATTER",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloCompare,
+        "STABLEHLO_COMPARE",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloConvert,
+        "STABLEHLO_CONVERT",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloDynamicSlice,
+        "STABLEHLO_DYNAMIC_SLICE",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloDynamicUpdateSlice,
+        "STABLEHLO_DYNAMIC_UPDATE_SLICE",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloPad,
+        "STABLEHLO_PAD",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloIota,
+        "STABLEHLO_IOTA",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloDotGeneral,
+        "STABLEHLO_DOT_GENERAL",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloReduceWindow,
+        "STABLEHLO_REDUCE_WINDOW",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloSort,
+        "STABLEHLO_SORT",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloWhile,
+        "STABLEHLO_WHILE",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloGather,
+        "STABLEHLO_GATHER",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloTranspose,
+        "STABLEHLO_TRANSPOSE",
+    ),
+    (TfLiteBuiltinOperator_kTfLiteBuiltinDilate, "DILATE"),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloRngBitGenerator,
+        "STABLEHLO_RNG_BIT_GENERATOR",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinReduceWindow,
+        "REDUCE_WINDOW",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloComposite,
+        "STABLEHLO_COMPOSITE",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloShiftLeft,
+        "STABLEHLO_SHIFT_LEFT",
+    ),
+    (
+        TfLiteBuiltinOperator_kTfLiteBuiltinStablehloCbrt,
+        "STABLEHLO_CBRT",
+    ),
+];
+
+/// The `BuiltinOperator` code of custom operators.
+const CUSTOM_OPERATOR_CODE: i32 = TfLiteBuiltinOperator_kTfLiteBuiltinCustom as i32;
+
+/// Returns the name of the builtin operator with the given code, e.g. `ADD` for `0`.
+pub fn builtin_operator_name(code: i32) -> Option<&'static str> {
+    BUILTIN_OPERATORS
+        .iter()
+        .find(|&&(builtin_code, _)| builtin_code as i32 == code)
+        .map(|&(_, name)| name)
+}
+
+/// Returns the code of the builtin operator with the given name, e.g. `0` for `ADD`.
+pub fn builtin_operator_code(name: &str) -> Option<i32> {
+    BUILTIN_OPERATORS
+        .iter()
+        .find(|&&(_, builtin_name)| builtin_name == name)
+        .map(|&(code, _)| code as i32)
+}
+
+/// Validates that a model only uses allowed operators and versions.
+///
+/// [`ModelValidator::validate()`] resolves all operators of a model and fails with
+/// [`ErrorKind::DisallowedOperator`] naming the first operator which is not allowed. Set it
+/// with [`OptionsBuilder::model_validator()`][crate::interpreter::OptionsBuilder::model_validator]
+/// to validate the model before its interpreters are created, including those of pools, caches
+/// and workers.
+///
+/// - Note: TensorFlow Lite C API does not expose the builtin kernels, so an operator resolver
+/// cannot be installed on the interpreters of a model without replacing all of their kernels.
+/// Instead, the operators are resolved by building the graph once with placeholder kernels
+/// through `TfLiteInterpreterOptionsSetOpResolver`.
+///
+/// # Examples
+///
+/// ```
+/// use tflitec::interpreter::{Interpreter, Options};
+/// use tflitec::model::Model;
+/// use tflitec::validator::ModelValidator;
+/// let validator = ModelValidator::new().allow("ADD", 1..=4)?.allow("MUL", ..)?;
+/// let model = Model::new("tests/add.bin")?;
+/// validator.validate(&model)?;
+/// let options = Options::builder().model_validator(validator).build()?;
+/// let interpreter = Interpreter::new(&model, Some(options))?;
+/// # Ok::<(), tflitec::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ModelValidator {
+    /// The allowed inclusive version ranges, keyed by builtin operator code.
+    allowed: HashMap<i32, Vec<(i32, i32)>>,
+
+    /// The allowed inclusive version ranges, keyed by custom operator name.
+    allowed_custom: HashMap<String, Vec<(i32, i32)>>,
+}
+
+impl ModelValidator {
+    /// Creates a new validator which does not allow any operator.
+    pub fn new() -> ModelValidator {
+        ModelValidator::default()
+    }
+
+    /// Allows the given `versions` of the builtin operator with the given name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: Name of the builtin operator in the TensorFlow Lite schema, e.g. `CONV_2D`
+    /// * `versions`: Allowed versions of the operator, e.g. `1..=3` or `..` for all versions
+    ///
+    /// # Errors
+    ///
+    /// Returns error if `name` is not a builtin operator.
+    pub fn allow<R: RangeBounds<i32>>(mut self, name: &str, versions: R) -> Result<Self> {
+        let code = builtin_operator_code(name)
+            .filter(|&code| code != CUSTOM_OPERATOR_CODE)
+            .ok_or_else(|| Error::new(ErrorKind::UnknownOperator))?;
+        self.allowed
+            .entry(code)
+            .or_default()
+            .push(version_range(versions));
+        Ok(self)
+    }
+
+    /// Allows the given `versions` of the custom operator with the given name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: Name of the custom operator in the model, e.g. `TFLite_Detection_PostProcess`
+    /// * `versions`: Allowed versions of the operator, e.g. `1..=3` or `..` for all versions
+    pub fn allow_custom<R: RangeBounds<i32>>(mut self, name: &str, versions: R) -> Self {
+        self.allowed_custom
+            .entry(name.to_string())
+            .or_default()
+            .push(version_range(versions));
+        self
+    }
+
+    /// Returns whether the given version of the builtin operator with the given code
+    /// is allowed.
+    pub fn is_allowed(&self, code: i32, version: i32) -> bool {
+        contains_version(self.allowed.get(&code), version)
+    }
+
+    /// Returns whether the given version of the custom operator with the given name is allowed.
+    pub fn is_custom_allowed(&self, name: &str, version: i32) -> bool {
+        contains_version(self.allowed_custom.get(name), version)
+    }
+
+    /// Checks that all operators of the given model are allowed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ErrorKind::DisallowedOperator`] naming the first operator which is
+    /// not allowed, or error if TensorFlow Lite C fails internally. The name of a disallowed
+    /// custom operator is given by [`Error::requested_name()`].
+    pub fn validate(&self, model: &Model) -> Result<()> {
+        let mut resolution = Resolution {
+            validator: self,
+            registrations: HashMap::new(),
+            disallowed: None,
+        };
+        unsafe {
+            let options_ptr = TfLiteInterpreterOptionsCreate();
+            if options_ptr.is_null() {
+                return Err(Error::new(ErrorKind::FailedToCreateInterpreter));
+            }
+            // Unresolved operators are reported with `ErrorKind::DisallowedOperator` instead.
+            TfLiteInterpreterOptionsSetErrorReporter(
+                options_ptr,
+                Some(ignore_error),
+                std::ptr::null_mut(),
+            );
+            TfLiteInterpreterOptionsSetOpResolver(
+                options_ptr,
+                Some(find_builtin_op),
+                Some(find_custom_op),
+                &mut resolution as *mut Resolution as *mut c_void,
+            );
+            let interpreter_ptr =
+                TfLiteInterpreterCreate(model.model_ptr as *const TfLiteModel, options_ptr);
+            TfLiteInterpreterOptionsDelete(options_ptr);
+            if !interpreter_ptr.is_null() {
+                TfLiteInterpreterDelete(interpreter_ptr);
+            }
+            match resolution.disallowed {
+                Some(Disallowed::Builtin(code, version)) => {
+                    Err(Error::new(ErrorKind::DisallowedOperator(code, version)))
+                }
+                Some(Disallowed::Custom(name, version)) => Err(Error::disallowed_custom_operator(
+                    &name,
+                    version,
+                    self.allowed_custom.keys().cloned().collect(),
+                )),
+                None if interpreter_ptr.is_null() => {
+                    Err(Error::new(ErrorKind::FailedToCreateInterpreter))
+                }
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+/// Returns the inclusive bounds of the given version range.
+fn version_range<R: RangeBounds<i32>>(versions: R) -> (i32, i32) {
+    let min_version = match versions.start_bound() {
+        Bound::Included(&version) => version,
+        Bound::Excluded(&version) => version.saturating_add(1),
+        Bound::Unbounded => i32::MIN,
+    };
+    let max_version = match versions.end_bound() {
+        Bound::Included(&version) => version,
+        Bound::Excluded(&version) => version.saturating_sub(1),
+        Bound::Unbounded => i32::MAX,
+    };
+    (min_version, max_version)
+}
+
+fn contains_version(ranges: Option<&Vec<(i32, i32)>>, version: i32) -> bool {
+    ranges
+        .into_iter()
+        .flatten()
+        .any(|&(min_version, max_version)| (min_version..=max_version).contains(&version))
+}
+
+/// The first operator which is not allowed by a [`ModelValidator`].
+enum Disallowed {
+    Builtin(i32, i32),
+    Custom(String, i32),
+}
+
+/// The state of resolving the operators of a model in [`ModelValidator::validate()`].
+struct Resolution<'a> {
+    validator: &'a ModelValidator,
+
+    /// The placeholder kernels of the allowed operators, keyed by code, custom name and
+    /// version.
+    registrations: HashMap<(i32, Option<String>, i32), Box<TfLiteRegistration>>,
+
+    disallowed: Option<Disallowed>,
+}
+
+impl Resolution<'_> {
+    /// Returns the placeholder kernel of the given allowed operator.
+    unsafe fn registration(
+        &mut self,
+        code: i32,
+        custom_name: Option<String>,
+        version: i32,
+    ) -> *const TfLiteRegistration {
+        let registration = self
+            .registrations
+            .entry((code, custom_name, version))
+            .or_insert_with(|| {
+                // A kernel without any function is enough to build the graph.
+                let mut registration: Box<TfLiteRegistration> = Box::new(std::mem::zeroed());
+                registration.builtin_code = code;
+                registration.version = version;
+                registration
+            });
+        registration.as_ref() as *const TfLiteRegistration
+    }
+}
+
+unsafe extern "C" fn find_builtin_op(
+    user_data: *mut c_void,
+    op: TfLiteBuiltinOperator,
+    version: c_int,
+) -> *const TfLiteRegistration {
+    let resolution = &mut *(user_data as *mut Resolution);
+    let code = op as i32;
+    if !resolution.validator.is_allowed(code, version) {
+        resolution
+            .disallowed
+            .get_or_insert(Disallowed::Builtin(code, version));
+        return std::ptr::null();
+    }
+    resolution.registration(code, None, version)
+}
+
+unsafe extern "C" fn find_custom_op(
+    user_data: *mut c_void,
+    custom_op: *const c_char,
+    version: c_int,
+) -> *const TfLiteRegistration {
+    let resolution = &mut *(user_data as *mut Resolution);
+    let name = if custom_op.is_null() {
+        String::new()
+    } else {
+        CStr::from_ptr(custom_op).to_string_lossy().into_owned()
+    };
+    if !resolution.validator.is_custom_allowed(&name, version) {
+        resolution
+            .disallowed
+            .get_or_insert(Disallowed::Custom(name, version));
+        return std::ptr::null();
+    }
+    resolution.registration(CUSTOM_OPERATOR_CODE, Some(name), version)
+}
+
+unsafe extern "C" fn ignore_error(
+    _user_data: *mut c_void,
+    _format: *const c_char,
+    _args: VaListArg,
+) {
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::{Interpreter, Options};
+    use crate::model::Model;
+    use crate::validator::{builtin_operator_code, builtin_operator_name, ModelValidator};
+    use crate::ErrorKind;
+
+    const MODEL_PATH: &str = "tests/add.bin";
+    const CUSTOM_MODEL_PATH: &str = "tests/custom.bin";
+
+    #[test]
+    fn test_model_validator() {
+        assert_eq!(builtin_operator_name(0), Some("ADD"));
+        assert_eq!(builtin_operator_name(-1), None);
+        assert_eq!(builtin_operator_code("CONV_2D"), Some(3));
+        assert_eq!(builtin_operator_code("STABLEHLO_CBRT"), Some(208));
+        assert_eq!(
+            ErrorKind::UnknownOperator,
+            ModelValidator::new()
+                .allow("CUSTOM", ..)
+                .err()
+                .unwrap()
+                .kind()
+        );
+
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let validator = ModelValidator::new().allow("MUL", ..).unwrap();
+        assert!(!validator.is_allowed(0, 1));
+        let err = validator.validate(&model).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::DisallowedOperator(0, _)));
+        assert!(err.to_string().contains("ADD"));
+        let options = Options::builder()
+            .model_validator(validator)
+            .build()
+            .expect("Cannot build options");
+        let err = Interpreter::new(&model, Some(options)).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::DisallowedOperator(0, _)));
+
+        let validator = ModelValidator::new().allow("ADD", ..).unwrap();
+        assert!(validator.is_allowed(0, 1));
+        validator.validate(&model).expect("Cannot validate model");
+        let options = Options::builder()
+            .model_validator(validator)
+            .build()
+            .expect("Cannot build options");
+        let interpreter =
+            Interpreter::new(&model, Some(options)).expect("Cannot create interpreter!");
+        interpreter
+            .allocate_tensors()
+            .expect("Cannot allocate tensors");
+        let data = (0..192).map(|x| x as f32).collect::<Vec<f32>>();
+        assert!(interpreter.copy(&data[..], 0).is_ok());
+        assert!(interpreter.invoke().is_ok());
+        let expected: Vec<f32> = data.iter().map(|e| e * 3.0).collect();
+        assert_eq!(expected, interpreter.output(0).unwrap().data::<f32>());
+    }
+
+    #[test]
+    fn test_model_validator_custom_operators() {
+        let model = Model::new(CUSTOM_MODEL_PATH).expect("Cannot load model from file!");
+        let validator = ModelValidator::new().allow_custom("Cube", ..);
+        let err = validator.validate(&model).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::DisallowedOperator(32, 1)));
+        assert_eq!(err.requested_name(), Some("Square"));
+        assert_eq!(err.available_names(), ["Cube"]);
+
+        let validator = validator.allow_custom("Square", 2..);
+        assert!(!validator.is_custom_allowed("Square", 1));
+        assert!(validator.validate(&model).is_err());
+
+        let validator = validator.allow_custom("Square", 1..=1);
+        assert!(validator.is_custom_allowed("Square", 1));
+        validator.validate(&model).expect("Cannot validate model");
+    }
+}
@@ -0,0 +1,189 @@
+//! Creation of the [`Interpreter`]s of pools, caches and workers, and the threads of workers
+//! owning their [`Model`]s and [`Interpreter`]s.
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+use crate::interpreter::{Interpreter, Options};
+use crate::model::{Model, ModelSource};
+use crate::tensor::Shape;
+use crate::{Error, ErrorKind, Result};
+
+/// Resizes the input tensors of `interpreter` at the given indices to their shapes, and
+/// allocates its tensors.
+pub(crate) fn prepare<'s>(
+    interpreter: &Interpreter,
+    shapes: impl IntoIterator<Item = (usize, &'s Shape)>,
+) -> Result<()> {
+    for (index, shape) in shapes {
+        interpreter.resize_input(index, shape.clone())?;
+    }
+    interpreter.allocate_tensors()
+}
+
+/// Returns the shapes of all input tensors of `interpreter`.
+pub(crate) fn input_shapes(interpreter: &Interpreter) -> Result<Vec<Shape>> {
+    (0..interpreter.input_tensor_count())
+        .map(|index| Ok(interpreter.input(index)?.shape().clone()))
+        .collect()
+}
+
+/// The [`Options`] and input shapes of [`Interpreter`]s which are created the same way.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct InterpreterSetup {
+    pub(crate) options: Option<Options>,
+    pub(crate) input_shapes: Vec<(usize, Shape)>,
+}
+
+impl InterpreterSetup {
+    /// Creates an interpreter of `model`, resizes its inputs and allocates its tensors.
+    pub(crate) fn create<'a>(&self, model: &'a Model<'a>) -> Result<Interpreter<'a>> {
+        let interpreter = Interpreter::new(model, self.options.clone())?;
+        prepare(
+            &interpreter,
+            self.input_shapes
+                .iter()
+                .map(|(index, shape)| (*index, shape)),
+        )?;
+        Ok(interpreter)
+    }
+}
+
+/// The configuration shared by the builders of [`ModelWorker`]s.
+#[derive(Debug)]
+pub(crate) struct WorkerConfig {
+    pub(crate) setup: InterpreterSetup,
+
+    /// The number of requests which can be queued for the workers.
+    pub(crate) queue_capacity: usize,
+}
+
+impl WorkerConfig {
+    pub(crate) fn new(queue_capacity: usize) -> Self {
+        WorkerConfig {
+            setup: InterpreterSetup::default(),
+            queue_capacity,
+        }
+    }
+
+    pub(crate) fn set_options(&mut self, options: Options) {
+        self.setup.options = Some(options);
+    }
+
+    pub(crate) fn set_queue_capacity(&mut self, queue_capacity: usize) {
+        self.queue_capacity = queue_capacity.max(1);
+    }
+
+    pub(crate) fn add_input_shape(&mut self, index: usize, shape: Shape) {
+        self.setup.input_shapes.push((index, shape));
+    }
+}
+
+/// A worker serving requests on a dedicated thread with its own [`Model`] and [`Interpreter`].
+pub(crate) trait ModelWorker: Send + 'static {
+    /// The value sent to the spawning thread when the interpreter is ready, and passed to
+    /// [`ModelWorker::serve()`].
+    type Ready: Clone + Send + 'static;
+
+    /// Returns how the interpreter of the worker is created.
+    fn setup(&self) -> &InterpreterSetup;
+
+    /// Returns the ready value of the created `interpreter`.
+    fn ready(interpreter: &Interpreter) -> Result<Self::Ready>;
+
+    /// Serves requests until the queue of the worker is closed.
+    fn serve(self, interpreter: &Interpreter, ready: Self::Ready);
+}
+
+/// Spawns a thread named `{name}-{index}` for each of `workers`, which loads a model from
+/// `source` and creates its interpreter before serving requests, and returns the ready values
+/// of all workers once they are ready.
+///
+/// # Errors
+///
+/// Returns error if a thread cannot be spawned, or a worker fails to load the model or to create
+/// its interpreter.
+pub(crate) fn spawn<W: ModelWorker>(
+    name: &str,
+    source: ModelSource,
+    workers: Vec<W>,
+) -> Result<Vec<W::Ready>> {
+    let worker_count = workers.len();
+    let source = Arc::new(source);
+    let (ready_sender, ready_receiver) = mpsc::channel();
+    for (index, worker) in workers.into_iter().enumerate() {
+        let source = source.clone();
+        let ready_sender = ready_sender.clone();
+        thread::Builder::new()
+            .name(format!("{}-{}", name, index))
+            .spawn(move || run(worker, &source, ready_sender))
+            .map_err(|_| Error::new(ErrorKind::FailedToCreateInterpreter))?;
+    }
+    drop(ready_sender);
+    let readies = ready_receiver.iter().collect::<Result<Vec<_>>>()?;
+    if readies.len() < worker_count {
+        return Err(Error::new(ErrorKind::InferenceWorkerStopped));
+    }
+    Ok(readies)
+}
+
+/// Runs `worker` on the current thread, after sending whether its interpreter was created to
+/// `ready`.
+fn run<W: ModelWorker>(worker: W, source: &ModelSource, ready: mpsc::Sender<Result<W::Ready>>) {
+    let model = match source.load() {
+        Ok(model) => model,
+        Err(error) => {
+            let _ = ready.send(Err(error));
+            return;
+        }
+    };
+    let prepared = worker
+        .setup()
+        .create(&model)
+        .and_then(|interpreter| W::ready(&interpreter).map(|value| (interpreter, value)));
+    match prepared {
+        Ok((interpreter, value)) => {
+            let _ = ready.send(Ok(value.clone()));
+            drop(ready);
+            worker.serve(&interpreter, value);
+        }
+        Err(error) => {
+            let _ = ready.send(Err(error));
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::model::Model;
+    use crate::tensor::Shape;
+    use crate::worker::InterpreterSetup;
+
+    const MODEL_PATH: &str = "tests/add.bin";
+
+    /// Returns the bytes of `data` in native byte order.
+    pub(crate) fn to_bytes(data: &[f32]) -> Vec<u8> {
+        data.iter().flat_map(|x| x.to_ne_bytes()).collect()
+    }
+
+    #[test]
+    fn test_interpreter_setup() {
+        let model = Model::new(MODEL_PATH).expect("Cannot load model from file!");
+        let setup = InterpreterSetup {
+            options: None,
+            input_shapes: vec![(0, Shape::new(vec![2, 8, 8, 3]))],
+        };
+        let interpreter = setup.create(&model).unwrap();
+        assert_eq!(
+            interpreter.input(0).unwrap().shape().dimensions(),
+            &vec![2, 8, 8, 3]
+        );
+        let data = (0..2 * 8 * 8 * 3).map(|x| x as f32).collect::<Vec<f32>>();
+        interpreter.copy(&data, 0).unwrap();
+        interpreter.invoke().unwrap();
+        let expected = data.iter().map(|x| x * 3.0).collect::<Vec<f32>>();
+        assert_eq!(
+            interpreter.output(0).unwrap().data::<u8>(),
+            to_bytes(&expected).as_slice()
+        );
+    }
+}
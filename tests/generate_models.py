@@ -0,0 +1,340 @@
+#!/usr/bin/env python3
+"""Generates the small TensorFlow Lite test models in this directory.
+
+The models are serialized with a minimal FlatBuffers writer, so neither TensorFlow nor the
+flatbuffers package is required. Run it from the repository root:
+
+    python3 tests/generate_models.py
+"""
+import os
+import struct
+
+# Field types
+U8, I8, U32, I32, BOOL = "B", "b", "I", "i", "?"
+
+# Values of the TensorFlow Lite schema
+FLOAT32 = 0
+BUILTIN_ADD = 0
+BUILTIN_FULLY_CONNECTED = 9
+BUILTIN_RNN = 24
+BUILTIN_CUSTOM = 32
+OPTIONS_ADD = 11
+
+
+class Table:
+    """A table whose fields are `(field_id, kind, value)` tuples.
+
+    `kind` is a scalar struct format, "string", "table", "[table]", "[i32]" or "[u8]".
+    """
+
+    def __init__(self, *fields):
+        self.fields = [field for field in fields if field[2] is not None]
+
+
+class Writer:
+    """Writes objects front to back, so that all offsets point forward."""
+
+    def __init__(self):
+        self.data = bytearray()
+
+    def align(self, alignment, offset=0):
+        while (len(self.data) + offset) % alignment:
+            self.data.append(0)
+
+    def patch(self, position, target):
+        struct.pack_into("<I", self.data, position, target - position)
+
+    def finish(self, root, identifier):
+        self.data += struct.pack("<I", 0) + identifier
+        self.patch(0, self.table(root))
+        return bytes(self.data)
+
+    def table(self, table):
+        fields = sorted(table.fields, key=lambda field: field[0])
+        slot_count = fields[-1][0] + 1 if fields else 0
+
+        # The vtable precedes the table, the layout of the table is computed first.
+        layout, size = [], 4
+        for field_id, kind, value in fields:
+            width = struct.calcsize("<" + kind) if len(kind) == 1 else 4
+            size += -size % width
+            layout.append((field_id, kind, value, size))
+            size += width
+        slots = [0] * slot_count
+        for field_id, _, _, offset in layout:
+            slots[field_id] = offset
+
+        self.align(2)
+        vtable = len(self.data)
+        self.data += struct.pack("<HH", 4 + 2 * slot_count, size)
+        self.data += struct.pack("<%dH" % slot_count, *slots)
+        self.align(4)
+        position = len(self.data)
+        self.data += struct.pack("<i", position - vtable) + bytes(size - 4)
+
+        references = []
+        for _, kind, value, offset in layout:
+            if len(kind) == 1:
+                struct.pack_into("<" + kind, self.data, position + offset, value)
+            else:
+                references.append((position + offset, kind, value))
+        for field_position, kind, value in references:
+            self.patch(field_position, self.reference(kind, value))
+        return position
+
+    def reference(self, kind, value):
+        if kind == "string":
+            self.align(4)
+            position = len(self.data)
+            encoded = value.encode()
+            self.data += struct.pack("<I", len(encoded)) + encoded + b"\0"
+            return position
+        if kind == "table":
+            return self.table(value)
+        if kind == "[u8]":
+            # Buffers are aligned to 16 bytes.
+            self.align(16, 4)
+            position = len(self.data)
+            self.data += struct.pack("<I", len(value)) + bytes(value)
+            return position
+        if kind == "[i32]":
+            self.align(4)
+            position = len(self.data)
+            self.data += struct.pack("<I%di" % len(value), len(value), *value)
+            return position
+        if kind == "[table]":
+            self.align(4)
+            position = len(self.data)
+            self.data += struct.pack("<I", len(value)) + bytes(4 * len(value))
+            for index, element in enumerate(value):
+                self.patch(position + 4 + 4 * index, self.table(element))
+            return position
+        raise ValueError(kind)
+
+
+def floats(*values):
+    return struct.pack("<%df" % len(values), *values)
+
+
+def tensor(name, shape, buffer=0, is_variable=None):
+    return Table(
+        (0, "[i32]", shape),
+        (1, I8, FLOAT32),
+        (2, U32, buffer),
+        (3, "string", name),
+        (5, BOOL, is_variable),
+    )
+
+
+def operator_code(builtin_code, custom_code=None):
+    return Table((0, I8, builtin_code), (1, "string", custom_code), (3, I32, builtin_code))
+
+
+def operator(opcode_index, inputs, outputs, options_type=None, options=None):
+    return Table(
+        (0, U32, opcode_index),
+        (1, "[i32]", inputs),
+        (2, "[i32]", outputs),
+        (3, U8, options_type),
+        (4, "table", options),
+    )
+
+
+def subgraph(name, tensors, inputs, outputs, operators):
+    return Table(
+        (0, "[table]", tensors),
+        (1, "[i32]", inputs),
+        (2, "[i32]", outputs),
+        (3, "[table]", operators),
+        (4, "string", name),
+    )
+
+
+def model(description, operator_codes, subgraphs, buffers, signature_defs=None):
+    return Table(
+        (0, U32, 3),
+        (1, "[table]", operator_codes),
+        (2, "[table]", subgraphs),
+        (3, "string", description),
+        (4, "[table]", [Table((0, "[u8]", data)) for data in buffers]),
+        (7, "[table]", signature_defs),
+    )
+
+
+def tensor_map(name, tensor_index):
+    return Table((0, "string", name), (1, U32, tensor_index))
+
+
+def signature_def(key, inputs, outputs, subgraph_index):
+    return Table(
+        (0, "[table]", inputs),
+        (1, "[table]", outputs),
+        (2, "string", key),
+        (4, U32, subgraph_index),
+    )
+
+
+def stateful_model():
+    """An RNN cell accumulating its inputs, `state = input + state` and `output = state`.
+
+    The state is kept in a variable tensor between invocations.
+    """
+    tensors = [
+        tensor("input", [1, 1]),
+        tensor("weights", [1, 1], buffer=1),
+        tensor("recurrent_weights", [1, 1], buffer=2),
+        tensor("bias", [1], buffer=3),
+        tensor("state", [1, 1], is_variable=True),
+        tensor("output", [1, 1]),
+    ]
+    return model(
+        "Accumulator",
+        [operator_code(BUILTIN_RNN)],
+        [
+            subgraph(
+                "main",
+                tensors,
+                [0],
+                [5],
+                [operator(0, [0, 1, 2, 3, 4], [5])],
+            )
+        ],
+        [b"", floats(1.0), floats(1.0), floats(0.0)],
+    )
+
+
+def fully_connected_model():
+    """A fully connected layer with constant weights, `output = 3 * input`.
+
+    XNNPACK packs its weights, so it exercises weight caches.
+    """
+    tensors = [
+        tensor("input", [1, 4]),
+        tensor("weights", [4, 4], buffer=1),
+        tensor("bias", [4], buffer=2),
+        tensor("output", [1, 4]),
+    ]
+    weights = [3.0 if row == column else 0.0 for row in range(4) for column in range(4)]
+    return model(
+        "Fully connected",
+        [operator_code(BUILTIN_FULLY_CONNECTED)],
+        [subgraph("main", tensors, [0], [3], [operator(0, [0, 1, 2], [3])])],
+        [b"", floats(*weights), floats(0.0, 0.0, 0.0, 0.0)],
+    )
+
+
+def custom_model():
+    """A custom operator `Square` which is not implemented, `output = input * input`.
+
+    It is only meant to be validated, interpreters of it cannot be created.
+    """
+    tensors = [tensor("input", [1, 4]), tensor("output", [1, 4])]
+    return model(
+        "Custom",
+        [operator_code(BUILTIN_CUSTOM, custom_code="Square")],
+        [subgraph("main", tensors, [0], [1], [operator(0, [0], [1])])],
+        [b""],
+    )
+
+
+def broadcast_model():
+    """Adds a row to each row of a batch, `output = input + row`.
+
+    The batch sizes of its inputs differ, so it cannot be batched.
+    """
+    tensors = [tensor("input", [2, 4]), tensor("row", [1, 4]), tensor("output", [2, 4])]
+    return model(
+        "Broadcast add",
+        [operator_code(BUILTIN_ADD)],
+        [
+            subgraph(
+                "main",
+                tensors,
+                [0, 1],
+                [2],
+                [operator(0, [0, 1], [2], OPTIONS_ADD, Table())],
+            )
+        ],
+        [b""],
+    )
+
+
+def constant_batch_model():
+    """Adds its input to each row of a constant batch, `output = constant + input`.
+
+    The batch size of its output is the one of the constant, so it cannot be batched unless
+    its input has the same batch size.
+    """
+    tensors = [
+        tensor("input", [1, 4]),
+        tensor("constant", [2, 4], buffer=1),
+        tensor("output", [2, 4]),
+    ]
+    return model(
+        "Constant batch add",
+        [operator_code(BUILTIN_ADD)],
+        [
+            subgraph(
+                "main",
+                tensors,
+                [0],
+                [2],
+                [operator(0, [0, 1], [2], OPTIONS_ADD, Table())],
+            )
+        ],
+        [b"", floats(0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0)],
+    )
+
+
+def signature_model():
+    """Adds its inputs, `sum = x + y`, exported as the signature `add`.
+
+    The names of its inputs and outputs in the signature differ from the names of their
+    tensors.
+    """
+    tensors = [
+        tensor("serving_x:0", [1, 4]),
+        tensor("serving_y:0", [1, 4]),
+        tensor("StatefulPartitionedCall:0", [1, 4]),
+    ]
+    return model(
+        "Signature add",
+        [operator_code(BUILTIN_ADD)],
+        [
+            subgraph(
+                "main",
+                tensors,
+                [0, 1],
+                [2],
+                [operator(0, [0, 1], [2], OPTIONS_ADD, Table())],
+            )
+        ],
+        [b""],
+        [
+            signature_def(
+                "add",
+                [tensor_map("x", 0), tensor_map("y", 1)],
+                [tensor_map("sum", 2)],
+                0,
+            )
+        ],
+    )
+
+
+def main():
+    directory = os.path.dirname(os.path.abspath(__file__))
+    models = [
+        ("stateful.bin", stateful_model()),
+        ("fully_connected.bin", fully_connected_model()),
+        ("custom.bin", custom_model()),
+        ("broadcast.bin", broadcast_model()),
+        ("signature.bin", signature_model()),
+        ("constant_batch.bin", constant_batch_model()),
+    ]
+    for name, root in models:
+        with open(os.path.join(directory, name), "wb") as file:
+            file.write(Writer().finish(root, b"TFL3"))
+
+
+if __name__ == "__main__":
+    main()
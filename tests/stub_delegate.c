@@ -0,0 +1,59 @@
+// A stub external delegate library used by the tests of `delegate::ExternalDelegate`.
+//
+// The delegate does not claim any node. Creation fails if option `fail` is `true`, and
+// applying the delegate fails if option `fail_prepare` is `true`.
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+#include <string.h>
+
+// Mirrors the layout of `TfLiteDelegate` in `tensorflow/lite/core/c/common.h`.
+typedef struct StubDelegate {
+  void* data_;
+  int (*Prepare)(void* context, struct StubDelegate* delegate);
+  void* CopyFromBufferHandle;
+  void* CopyToBufferHandle;
+  void* FreeBufferHandle;
+  int64_t flags;
+  void* opaque_delegate_builder;
+} StubDelegate;
+
+static int live_delegate_count = 0;
+
+// `kTfLiteOk` and `kTfLiteDelegateError` of `TfLiteStatus`.
+static int StubPrepare(void* context, StubDelegate* delegate) {
+  return delegate->data_ != NULL ? 2 : 0;
+}
+
+StubDelegate* tflite_plugin_create_delegate(char** options_keys,
+                                            char** options_values,
+                                            size_t num_options,
+                                            void (*report_error)(const char*)) {
+  int fail_prepare = 0;
+  for (size_t i = 0; i < num_options; ++i) {
+    if (strcmp(options_keys[i], "fail_prepare") == 0 &&
+        strcmp(options_values[i], "true") == 0) {
+      fail_prepare = 1;
+    }
+    if (strcmp(options_keys[i], "fail") == 0 &&
+        strcmp(options_values[i], "true") == 0) {
+      if (report_error != NULL) {
+        report_error("stub delegate failed on request");
+      }
+      return NULL;
+    }
+  }
+  StubDelegate* delegate = calloc(1, sizeof(StubDelegate));
+  delegate->Prepare = StubPrepare;
+  // Marks the delegate failing to prepare.
+  delegate->data_ = fail_prepare ? delegate : NULL;
+  ++live_delegate_count;
+  return delegate;
+}
+
+void tflite_plugin_destroy_delegate(StubDelegate* delegate) {
+  free(delegate);
+  --live_delegate_count;
+}
+
+int stub_delegate_live_count(void) { return live_delegate_count; }